@@ -0,0 +1,305 @@
+//! Typed batch of heterogeneous calls.
+
+use std::result;
+use futures::{Async, Future, Poll};
+use api::{Cita, Eth, Namespace, Net};
+use helpers::CallResult;
+use transports::Batch;
+use transports::batch::{BatchFuture, SingleResult};
+use {BatchTransport, Error, ErrorKind, RequestId};
+
+/// A single call within the batch, holding its result once resolved.
+pub struct Entry<F: Future> {
+    future: F,
+    result: Option<result::Result<F::Item, F::Error>>,
+}
+
+impl<F: Future> Entry<F> {
+    fn new(future: F) -> Self {
+        Entry { future, result: None }
+    }
+
+    fn poll_done(&mut self) -> bool {
+        if self.result.is_some() {
+            return true;
+        }
+
+        match self.future.poll() {
+            Ok(Async::NotReady) => false,
+            Ok(Async::Ready(value)) => {
+                self.result = Some(Ok(value));
+                true
+            }
+            Err(err) => {
+                self.result = Some(Err(err));
+                true
+            }
+        }
+    }
+
+    fn take(&mut self) -> result::Result<F::Item, F::Error> {
+        self.result.take().expect("take is called only after poll_done returned true; qed")
+    }
+}
+
+/// Tuple of calls added to the batch.
+pub trait Handles {
+    /// Tuple of results of all calls.
+    type Output;
+
+    /// Polls all calls and returns `true` if all of them are resolved.
+    fn poll_all(&mut self) -> bool;
+
+    /// Takes results of all calls.
+    fn take(&mut self) -> Self::Output;
+}
+
+/// Tuple of calls which can be extended with another call.
+pub trait Append<F> {
+    /// Tuple with the call appended.
+    type Out;
+
+    /// Appends the call.
+    fn append(self, call: F) -> Self::Out;
+}
+
+macro_rules! impl_handles {
+  ($($name: ident : $idx: tt),*) => {
+    impl<$($name: Future,)*> Handles for ($(Entry<$name>,)*) {
+      type Output = ($(result::Result<$name::Item, $name::Error>,)*);
+
+      #[allow(unused_mut)]
+      fn poll_all(&mut self) -> bool {
+        let mut done = true;
+        $(done &= self.$idx.poll_done();)*
+        done
+      }
+
+      fn take(&mut self) -> Self::Output {
+        ($(self.$idx.take(),)*)
+      }
+    }
+
+    impl<Z: Future, $($name: Future,)*> Append<Z> for ($(Entry<$name>,)*) {
+      type Out = ($(Entry<$name>,)* Entry<Z>,);
+
+      fn append(self, call: Z) -> Self::Out {
+        ($(self.$idx,)* Entry::new(call),)
+      }
+    }
+  }
+}
+
+impl_handles!();
+impl_handles!(A: 0);
+impl_handles!(A: 0, B: 1);
+impl_handles!(A: 0, B: 1, C: 2);
+impl_handles!(A: 0, B: 1, C: 2, D: 3);
+impl_handles!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_handles!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_handles!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_handles!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+impl_handles!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+impl_handles!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+
+/// Builder of a batch of calls keeping the output type of each call.
+///
+/// Calls have to be created using namespaces of this batch (see `api`, `cita`, ...),
+/// calls of other batches are rejected by `add`.
+/// Only added calls are sent, other calls created using namespaces of the batch fail.
+/// Up to 10 calls can be added to a single batch.
+#[derive(Debug)]
+pub struct TypedBatch<T: BatchTransport, H> {
+    transport: Batch<T>,
+    ids: Vec<RequestId>,
+    handles: H,
+}
+
+impl<T: BatchTransport> TypedBatch<T, ()> {
+    /// Creates new empty batch.
+    pub fn new(transport: T) -> Self {
+        TypedBatch {
+            transport: Batch::new(transport),
+            ids: vec![],
+            handles: (),
+        }
+    }
+}
+
+impl<T: BatchTransport, H> TypedBatch<T, H> {
+    /// Borrows the batching transport.
+    pub fn transport(&self) -> &Batch<T> {
+        &self.transport
+    }
+
+    /// Access methods from custom namespace within the batch.
+    pub fn api<A: Namespace<Batch<T>>>(&self) -> A {
+        A::new(self.transport.clone())
+    }
+
+    /// Access methods from `cita` namespace within the batch.
+    pub fn cita(&self) -> Cita<Batch<T>> {
+        self.api()
+    }
+
+    /// Access methods from `eth` namespace within the batch.
+    pub fn eth(&self) -> Eth<Batch<T>> {
+        self.api()
+    }
+
+    /// Access methods from `net` namespace within the batch.
+    pub fn net(&self) -> Net<Batch<T>> {
+        self.api()
+    }
+
+    /// Adds a call to the batch.
+    ///
+    /// Fails if the call was created using namespaces of another batch.
+    pub fn add<R>(self, call: CallResult<R, SingleResult>) -> Result<TypedBatch<T, H::Out>, Error>
+    where
+        H: Append<CallResult<R, SingleResult>>,
+    {
+        if !self.transport.owns(call.inner()) {
+            return Err(ErrorKind::Transport("Call was created using namespaces of another batch.".into()).into());
+        }
+
+        let mut ids = self.ids;
+        ids.push(call.inner().id());
+        Ok(TypedBatch {
+            transport: self.transport,
+            ids,
+            handles: self.handles.append(call),
+        })
+    }
+
+    /// Sends the batch.
+    ///
+    /// Resolves to a tuple with a separate result of each call.
+    /// Failure of the whole batch is reported as an error of every call.
+    pub fn execute(self) -> BatchExecution<T::Batch, H>
+    where
+        H: Handles,
+    {
+        BatchExecution {
+            batch: Some(self.transport.submit_only(&self.ids)),
+            handles: self.handles,
+        }
+    }
+}
+
+/// A future resolving to results of all calls within the batch.
+pub struct BatchExecution<B, H> {
+    batch: Option<BatchFuture<B>>,
+    handles: H,
+}
+
+impl<B, H> Future for BatchExecution<B, H>
+where
+    B: Future<Item = Vec<result::Result<::rpc::Value, Error>>, Error = Error>,
+    H: Handles,
+{
+    type Item = H::Output;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(mut batch) = self.batch.take() {
+            match batch.poll() {
+                Ok(Async::NotReady) => {
+                    self.batch = Some(batch);
+                    return Ok(Async::NotReady);
+                }
+                // The error is already delivered to every call.
+                Ok(Async::Ready(_)) | Err(_) => {}
+            }
+        }
+
+        if self.handles.poll_all() {
+            Ok(Async::Ready(self.handles.take()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Future;
+    use rpc::Value;
+    use api::Web3;
+    use helpers::tests::TestTransport;
+    use types::U256;
+    use ErrorKind;
+
+    #[test]
+    fn should_resolve_typed_results() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let result = {
+            let batch = Web3::new(&transport).batch();
+            let cita = batch.cita();
+            let net = batch.net();
+
+            // when
+            batch
+                .add(cita.block_number())
+                .and_then(|batch| batch.add(net.peer_count()))
+                .and_then(|batch| batch.add(cita.block_number()))
+                .unwrap()
+                .execute()
+                .wait()
+        };
+
+        // then
+        let (height, peers, failed) = result.unwrap();
+        assert_eq!(height, Ok(U256::from(0x10)));
+        assert_eq!(peers, Ok("0x2".to_owned()));
+        assert_eq!(failed, Err(ErrorKind::Unreachable.into()));
+        transport.assert_request("cita_blockNumber", &[]);
+        transport.assert_request("net_peerCount", &[]);
+        transport.assert_request("cita_blockNumber", &[]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_reject_calls_of_other_batches() {
+        // given
+        let transport = TestTransport::default();
+        let web3 = Web3::new(&transport);
+        let other = web3.batch();
+
+        // when
+        let result = web3.batch().add(other.cita().block_number());
+
+        // then
+        assert_eq!(
+            result.err(),
+            Some(ErrorKind::Transport("Call was created using namespaces of another batch.".into()).into())
+        );
+    }
+
+    #[test]
+    fn should_not_send_calls_which_were_not_added() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        let (result, forgotten) = {
+            let batch = Web3::new(&transport).batch();
+            let cita = batch.cita();
+            let forgotten = cita.block_number();
+
+            // when
+            let result = batch.add(cita.block_number()).unwrap().execute().wait();
+            (result, forgotten.wait())
+        };
+
+        // then
+        // the only response went to the added call, so the forgotten one was not sent
+        assert_eq!(result.unwrap().0, Ok(U256::from(0x10)));
+        assert_eq!(
+            forgotten,
+            Err(ErrorKind::Transport("Call was not added to the batch.".into()).into())
+        );
+    }
+}
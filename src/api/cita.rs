@@ -2,7 +2,7 @@
 #![allow(dead_code, unused_imports)]
 use api::Namespace;
 use helpers::{self, CallResult};
-use types::{Address, BlockId, BlockNumber, Bytes, H160, H256, U256, Work};
+use types::{Address, BlockId, BlockNumber, Bytes, H160, H256, SyncState, U256, Work};
 use Transport;
 use cita_types::*;
 use cita_crypto::*;
@@ -214,9 +214,8 @@ impl<T: Transport> Cita<T> {
         tx.sign(*pk).take_transaction_with_sig()
     }
 
-    // TODO [ToDr] Proper type?
     /// Get syncing status
-    fn syncing(&self) -> CallResult<bool, T::Out> {
+    pub fn syncing(&self) -> CallResult<SyncState, T::Out> {
         CallResult::new(self.transport.execute("eth_syncing", vec![]))
     }
 }
@@ -2,8 +2,8 @@
 
 use api::Namespace;
 use helpers::{self, CallResult};
-use types::{Address, Block, BlockId, BlockNumber, Bytes, CallRequest, H256, H520, H64, Index, Transaction,
-            TransactionId, TransactionReceipt, TransactionRequest, U256, Work};
+use types::{Address, Block, BlockId, BlockNumber, Bytes, CallRequest, H256, H520, H64, Index, SyncState,
+            Transaction, TransactionId, TransactionReceipt, TransactionRequest, U256, Work};
 use Transport;
 
 /// `Eth` namespace
@@ -328,9 +328,8 @@ impl<T: Transport> Eth<T> {
         )
     }
 
-    // TODO [ToDr] Proper type?
     /// Get syncing status
-    pub fn syncing(&self) -> CallResult<bool, T::Out> {
+    pub fn syncing(&self) -> CallResult<SyncState, T::Out> {
         CallResult::new(self.transport.execute("eth_syncing", vec![]))
     }
 }
@@ -340,8 +339,8 @@ mod tests {
     use futures::Future;
 
     use api::Namespace;
-    use types::{Block, BlockId, BlockNumber, Bytes, CallRequest, H256, Transaction, TransactionId, TransactionReceipt,
-                TransactionRequest, Work};
+    use types::{Block, BlockId, BlockNumber, Bytes, CallRequest, H256, SyncState, Transaction, TransactionId,
+                TransactionReceipt, TransactionRequest, Work};
     use rpc::Value;
 
     use super::Eth;
@@ -695,6 +694,18 @@ mod tests {
 
     rpc_test! (
     Eth:syncing => "eth_syncing";
-    Value::Bool(true) => true
+    Value::Bool(false) => SyncState::NotSyncing
+  );
+
+    rpc_test! (
+    Eth:syncing:syncing_in_progress => "eth_syncing";
+    json!({"startingBlock": "0x384", "currentBlock": "0x386", "highestBlock": "0x454"})
+      => SyncState::Syncing {
+        starting_block: 0x384.into(),
+        current_block: 0x386.into(),
+        highest_block: 0x454.into(),
+        known_states: None,
+        pulled_states: None,
+      }
   );
 }
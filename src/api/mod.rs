@@ -1,22 +1,28 @@
 //! `Web3` implementation
 
+mod batch;
 mod eth;
 mod eth_filter;
 mod net;
 mod personal;
+mod sync;
 mod web3;
 mod cita;
 
+pub use self::batch::{Append, BatchExecution, Entry, Handles, TypedBatch};
 pub use self::cita::Cita;
 pub use self::eth::Eth;
 pub use self::eth_filter::{BaseFilter, CreateFilter, EthFilter, FilterStream};
 pub use self::net::Net;
 pub use self::personal::Personal;
+pub use self::sync::WaitUntilSynced;
 pub use self::web3::Web3 as Web3Api;
 
 use std::time::Duration;
 use futures::IntoFuture;
-use {confirm, Error, Transport};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use {confirm, helpers, BatchTransport, Error, Transport};
 use types::{TransactionRequest, U256};
 
 /// Common API for all namespaces
@@ -80,6 +86,25 @@ impl<T: Transport> Web3<T> {
         self.api()
     }
 
+    /// Calls any RPC method and decodes the result
+    ///
+    /// See `helpers::to_params` for conversion of `params`.
+    pub fn call_method<R, P>(&self, method: &str, params: P) -> helpers::CallResult<R, T::Out>
+    where
+        R: DeserializeOwned,
+        P: Serialize,
+    {
+        helpers::CallResult::new(self.transport.execute(method, helpers::to_params(&params)))
+    }
+
+    /// Creates a batch of calls with typed results
+    pub fn batch(&self) -> TypedBatch<T, ()>
+    where
+        T: BatchTransport,
+    {
+        TypedBatch::new(self.transport.clone())
+    }
+
     /// Should be used to wait for confirmations
     pub fn wait_for_confirmations<F, V>(
         &self,
@@ -100,6 +125,11 @@ impl<T: Transport> Web3<T> {
         )
     }
 
+    /// Returns future resolved once the node is no longer syncing, checked every `poll_interval`
+    pub fn wait_until_synced(&self, poll_interval: Duration) -> WaitUntilSynced<T> {
+        WaitUntilSynced::new(self.transport.clone(), poll_interval)
+    }
+
     /// Sends transaction and returns future resolved after transaction is confirmed
     pub fn send_transaction_with_confirmation(
         &self,
@@ -110,3 +140,87 @@ impl<T: Transport> Web3<T> {
         confirm::send_transaction_with_confirmation(self.transport.clone(), tx, poll_interval, confirmations)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::Future;
+    use rpc::Value;
+    use helpers::tests::TestTransport;
+    use types::{Address, BlockNumber, U256};
+    use super::Web3;
+
+    web3_namespace! {
+      /// Test namespace
+      TxPool {
+        /// Returns pending transactions count
+        fn status() -> U256 = "txpool_status";
+        /// Returns balance
+        fn balance(address: Address, block: BlockNumber) -> U256 = "txpool_balance";
+      }
+    }
+
+    #[test]
+    fn should_call_any_method() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        transport.add_response(Value::Bool(true));
+        let (count, balance, listening) = {
+            let web3 = Web3::new(&transport);
+
+            // when
+            (
+                web3.call_method::<U256, _>("txpool_count", ()).wait(),
+                web3.call_method::<U256, _>("eth_getBalance", (Address::from(5), BlockNumber::Latest))
+                    .wait(),
+                web3.call_method::<bool, _>("net_listening", "0x1").wait(),
+            )
+        };
+
+        // then
+        assert_eq!(count, Ok(U256::from(1)));
+        assert_eq!(balance, Ok(U256::from(2)));
+        assert_eq!(listening, Ok(true));
+        transport.assert_request("txpool_count", &[]);
+        transport.assert_request(
+            "eth_getBalance",
+            &[
+                r#""0x0000000000000000000000000000000000000005""#.into(),
+                r#""latest""#.into(),
+            ],
+        );
+        transport.assert_request("net_listening", &[r#""0x1""#.into()]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_define_namespace_with_macro() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        transport.add_response(Value::String("0x20".into()));
+        let (status, balance) = {
+            let pool = Web3::new(&transport).api::<TxPool<_>>();
+
+            // when
+            (
+                pool.status().wait(),
+                pool.balance(Address::from(5), BlockNumber::Number(1)).wait(),
+            )
+        };
+
+        // then
+        assert_eq!(status, Ok(U256::from(0x10)));
+        assert_eq!(balance, Ok(U256::from(0x20)));
+        transport.assert_request("txpool_status", &[]);
+        transport.assert_request(
+            "txpool_balance",
+            &[
+                r#""0x0000000000000000000000000000000000000005""#.into(),
+                r#""0x1""#.into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+    }
+}
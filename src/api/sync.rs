@@ -0,0 +1,89 @@
+//! Waiting for the node to finish syncing.
+
+use std::time::Duration;
+use futures::{Async, Future, Poll, Stream};
+use tokio_timer::{Interval, Timer};
+
+use api::{Eth, Namespace};
+use helpers::CallResult;
+use types::SyncState;
+use {Error, ErrorKind, Transport};
+
+#[derive(Debug)]
+enum State<O> {
+    Checking(CallResult<SyncState, O>),
+    WaitForInterval,
+}
+
+/// Future resolved once `eth_syncing` reports the node is synced.
+#[derive(Debug)]
+pub struct WaitUntilSynced<T: Transport> {
+    eth: Eth<T>,
+    interval: Interval,
+    state: State<T::Out>,
+}
+
+impl<T: Transport> WaitUntilSynced<T> {
+    /// Checks the sync status right away and then every `poll_interval` until synced.
+    pub fn new(transport: T, poll_interval: Duration) -> Self {
+        let eth = Eth::new(transport);
+        WaitUntilSynced {
+            state: State::Checking(eth.syncing()),
+            interval: Timer::default().interval(poll_interval),
+            eth,
+        }
+    }
+}
+
+impl<T: Transport> Future for WaitUntilSynced<T> {
+    type Item = ();
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let next_state = match self.state {
+                State::Checking(ref mut future) => match try_ready!(future.poll()) {
+                    SyncState::NotSyncing => return Ok(Async::Ready(())),
+                    SyncState::Syncing { .. } => State::WaitForInterval,
+                },
+                State::WaitForInterval => {
+                    let _ready = try_ready!(
+                        self.interval
+                            .poll()
+                            .map_err(|_| Error::from(ErrorKind::Unreachable))
+                    );
+                    State::Checking(self.eth.syncing())
+                }
+            };
+            self.state = next_state;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use futures::Future;
+    use rpc::Value;
+    use helpers::tests::TestTransport;
+    use super::WaitUntilSynced;
+
+    #[test]
+    fn should_wait_until_synced() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(json!({"startingBlock": "0x1", "currentBlock": "0x2", "highestBlock": "0x3"}));
+        transport.add_response(json!({"startingBlock": "0x1", "currentBlock": "0x3", "highestBlock": "0x4"}));
+        transport.add_response(Value::Bool(false));
+
+        // when
+        let result = WaitUntilSynced::new(&transport, Duration::from_millis(1)).wait();
+
+        // then
+        assert_eq!(result, Ok(()));
+        transport.assert_request("eth_syncing", &[]);
+        transport.assert_request("eth_syncing", &[]);
+        transport.assert_request("eth_syncing", &[]);
+        transport.assert_no_more_requests();
+    }
+}
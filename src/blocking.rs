@@ -0,0 +1,481 @@
+//! Blocking (synchronous) client.
+//!
+//! Wraps `Web3`, all namespaces and contracts with methods blocking the current thread
+//! until the response is received or the timeout expires.
+//! Transports created by `Web3::http` and `Web3::ipc` run on an internal event loop thread
+//! which is stopped when the last client sharing it is dropped.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use ethabi;
+use futures::Future;
+use futures::future::Either;
+use tokio_timer::Timer;
+use api;
+use cita_types::{Block as CitaBlock, CallRequest as CitaCallRequest, Receipt as CitaReceipt, RpcTransaction,
+                 TxResponse};
+use contract::{self, deploy, Options};
+use transports::timer::{cap_timeout, request_timer};
+use contract::tokens::{Detokenize, Tokenize};
+use types::{Address, Block, BlockId, BlockNumber, Bytes, CallRequest, H256, H520, H64, Index, SyncState,
+            Transaction, TransactionId, TransactionReceipt, TransactionRequest, U256, Work};
+use {Error, ErrorKind, Transport};
+
+/// Default time to wait for a response.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Blocks on futures with a timeout.
+#[derive(Clone)]
+struct Runtime {
+    timer: Timer,
+    timeout: Duration,
+    // Keeps the event loop running as long as any client uses it.
+    event_loop: Option<Arc<Send + Sync>>,
+}
+
+impl fmt::Debug for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Runtime")
+            .field("timeout", &self.timeout)
+            .field("event_loop", &self.event_loop.is_some())
+            .finish()
+    }
+}
+
+impl Runtime {
+    fn wait<F>(&self, future: F) -> Result<F::Item, F::Error>
+    where
+        F: Future,
+        F::Error: From<Error>,
+    {
+        match future.select2(self.timer.sleep(self.timeout)).wait() {
+            Ok(Either::A((item, _))) => Ok(item),
+            Ok(Either::B(_)) => Err(Error::from(ErrorKind::Timeout).into()),
+            Err(Either::A((err, _))) => Err(err),
+            Err(Either::B((err, _))) => {
+                Err(Error::from(ErrorKind::Transport(format!("Timer failed: {:?}", err))).into())
+            }
+        }
+    }
+}
+
+/// Blocking `Web3` client.
+#[derive(Debug, Clone)]
+pub struct Web3<T: Transport> {
+    web3: api::Web3<T>,
+    runtime: Runtime,
+}
+
+#[cfg(feature = "http")]
+impl Web3<::transports::Http> {
+    /// Creates blocking client connected to HTTP endpoint.
+    pub fn http(url: &str) -> ::error::Result<Self> {
+        let (event_loop, http) = ::transports::Http::new(url)?;
+        Ok(Self::with_event_loop(http, Arc::new(event_loop)))
+    }
+}
+
+#[cfg(feature = "ipc")]
+impl Web3<::transports::Ipc> {
+    /// Creates blocking client connected to IPC socket.
+    pub fn ipc<P: AsRef<::std::path::Path>>(path: P) -> ::error::Result<Self> {
+        let (event_loop, ipc) = ::transports::Ipc::new(path)?;
+        Ok(Self::with_event_loop(ipc, Arc::new(event_loop)))
+    }
+}
+
+impl<T: Transport> Web3<T> {
+    /// Creates blocking client given transport (its event loop has to be running).
+    pub fn new(transport: T) -> Self {
+        Web3 {
+            web3: api::Web3::new(transport),
+            runtime: Runtime {
+                timer: request_timer(),
+                timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+                event_loop: None,
+            },
+        }
+    }
+
+    fn with_event_loop(transport: T, event_loop: Arc<Send + Sync>) -> Self {
+        let mut web3 = Self::new(transport);
+        web3.runtime.event_loop = Some(event_loop);
+        web3
+    }
+
+    /// Sets time to wait for each response (at most `MAX_REQUEST_TIMEOUT_SECS`, longer timeouts are capped).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.runtime.timeout = cap_timeout(timeout);
+        self
+    }
+
+    /// Returns time to wait for each response.
+    pub fn timeout(&self) -> Duration {
+        self.runtime.timeout
+    }
+
+    /// Borrows the asynchronous client.
+    pub fn inner(&self) -> &api::Web3<T> {
+        &self.web3
+    }
+
+    /// Blocks until the future resolves or the timeout expires.
+    pub fn wait<F>(&self, future: F) -> Result<F::Item, F::Error>
+    where
+        F: Future,
+        F::Error: From<Error>,
+    {
+        self.runtime.wait(future)
+    }
+
+    /// Access methods from `cita` namespace
+    pub fn cita(&self) -> Cita<T> {
+        Cita {
+            api: self.web3.cita(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Access methods from `eth` namespace
+    pub fn eth(&self) -> Eth<T> {
+        Eth {
+            api: self.web3.eth(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Access methods from `net` namespace
+    pub fn net(&self) -> Net<T> {
+        Net {
+            api: self.web3.net(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Access methods from `web3` namespace
+    pub fn web3(&self) -> Web3Api<T> {
+        Web3Api {
+            api: self.web3.web3(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Access methods from `personal` namespace
+    pub fn personal(&self) -> Personal<T> {
+        Personal {
+            api: self.web3.personal(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Creates contract interface given its address and JSON ABI.
+    pub fn contract(&self, address: Address, json: &[u8]) -> Result<Contract<T>, ethabi::Error> {
+        Ok(Contract {
+            contract: contract::Contract::from_json(self.web3.eth(), address, json)?,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    /// Creates contract deployment builder given its JSON ABI.
+    pub fn deploy_builder(&self, json: &[u8]) -> Result<deploy::Builder<T>, ethabi::Error> {
+        contract::Contract::deploy(self.web3.eth(), json)
+    }
+
+    /// Deploys the contract and blocks until the deployment is confirmed.
+    pub fn deploy<P, V>(
+        &self,
+        builder: deploy::Builder<T>,
+        code: V,
+        params: P,
+        from: Address,
+    ) -> Result<Contract<T>, deploy::Error>
+    where
+        P: Tokenize,
+        V: Into<Vec<u8>>,
+    {
+        let pending = builder
+            .execute(code, params, from)
+            .map_err(|err| deploy::Error::from(format!("Invalid deployment: {}", err)))?;
+        Ok(Contract {
+            contract: self.runtime.wait(pending)?,
+            runtime: self.runtime.clone(),
+        })
+    }
+}
+
+macro_rules! blocking_namespace {
+  (
+    $(#[$attr: meta])*
+    $name: ident => $inner: ident {
+      $(
+        $(#[$method_attr: meta])*
+        fn $method: ident ( $($param: ident : $param_ty: ty),* ) -> $ret: ty;
+      )*
+    }
+  ) => {
+    $(#[$attr])*
+    #[derive(Debug, Clone)]
+    pub struct $name<T: Transport> {
+      api: api::$inner<T>,
+      runtime: Runtime,
+    }
+
+    impl<T: Transport> $name<T> {
+      /// Borrows the asynchronous namespace.
+      pub fn inner(&self) -> &api::$inner<T> {
+        &self.api
+      }
+
+      $(
+        $(#[$method_attr])*
+        pub fn $method(&self, $($param: $param_ty),*) -> Result<$ret, Error> {
+          self.runtime.wait(self.api.$method($($param),*))
+        }
+      )*
+    }
+  };
+}
+
+blocking_namespace! {
+  /// Blocking `cita` namespace
+  Cita => Cita {
+    /// Get current block number
+    fn block_number() -> U256;
+    /// Call a constant method of contract without changing the state of the blockchain.
+    fn call(req: CitaCallRequest, block: Option<BlockNumber>) -> Bytes;
+    /// Get block details with transaction hashes.
+    fn block(block: BlockId) -> CitaBlock;
+    /// Get block details with full transaction objects.
+    fn block_with_txs(block: BlockId) -> CitaBlock;
+    /// Get code under given address
+    fn code(address: Address, block: Option<BlockNumber>) -> Bytes;
+    /// Get nonce
+    fn transaction_count(address: Address, block: Option<BlockNumber>) -> U256;
+    /// Get transaction
+    fn transaction(hash: H256) -> Option<RpcTransaction>;
+    /// Get transaction receipt
+    fn transaction_receipt(hash: H256) -> Option<CitaReceipt>;
+    /// Create new block filter
+    fn new_block_filter() -> U256;
+    /// Sends a transaction
+    fn send_transaction(tx: String) -> TxResponse;
+  }
+}
+
+blocking_namespace! {
+  /// Blocking `eth` namespace
+  Eth => Eth {
+    /// Get list of available accounts.
+    fn accounts() -> Vec<Address>;
+    /// Get current block number
+    fn block_number() -> U256;
+    /// Call a constant method of contract without changing the state of the blockchain.
+    fn call(req: CallRequest, block: Option<BlockNumber>) -> Bytes;
+    /// Get coinbase address
+    fn coinbase() -> Address;
+    /// Call a contract without changing the state of the blockchain to estimate gas usage.
+    fn estimate_gas(req: CallRequest, block: Option<BlockNumber>) -> U256;
+    /// Get current recommended gas price
+    fn gas_price() -> U256;
+    /// Get balance of given address
+    fn balance(address: Address, block: Option<BlockNumber>) -> U256;
+    /// Get block details with transaction hashes.
+    fn block(block: BlockId) -> Block<H256>;
+    /// Get block details with full transaction objects.
+    fn block_with_txs(block: BlockId) -> Block<Transaction>;
+    /// Get number of transactions in block
+    fn block_transaction_count(block: BlockId) -> Option<U256>;
+    /// Get code under given address
+    fn code(address: Address, block: Option<BlockNumber>) -> Bytes;
+    /// Get supported compilers
+    fn compilers() -> Vec<String>;
+    /// Get storage entry
+    fn storage(address: Address, idx: U256, block: Option<BlockNumber>) -> H256;
+    /// Get nonce
+    fn transaction_count(address: Address, block: Option<BlockNumber>) -> U256;
+    /// Get transaction
+    fn transaction(id: TransactionId) -> Option<Transaction>;
+    /// Get transaction receipt
+    fn transaction_receipt(hash: H256) -> Option<TransactionReceipt>;
+    /// Get uncle
+    fn uncle(block: BlockId, index: Index) -> Option<Block<H256>>;
+    /// Get uncle count in block
+    fn uncle_count(block: BlockId) -> Option<U256>;
+    /// Get work package
+    fn work() -> Work;
+    /// Get hash rate
+    fn hashrate() -> U256;
+    /// Get mining status
+    fn mining() -> bool;
+    /// Start new block filter
+    fn new_block_filter() -> U256;
+    /// Start new pending transaction filter
+    fn new_pending_transaction_filter() -> U256;
+    /// Get protocol version
+    fn protocol_version() -> String;
+    /// Sends a rlp-encoded signed transaction
+    fn send_raw_transaction(rlp: Bytes) -> H256;
+    /// Sends a transaction transaction
+    fn send_transaction(tx: TransactionRequest) -> H256;
+    /// Signs a hash of given data
+    fn sign(address: Address, data: Bytes) -> H520;
+    /// Submit hashrate of external miner
+    fn submit_hashrate(rate: U256, id: H256) -> bool;
+    /// Submit work of external miner
+    fn submit_work(nonce: H64, pow_hash: H256, mix_hash: H256) -> bool;
+    /// Get syncing status
+    fn syncing() -> SyncState;
+  }
+}
+
+blocking_namespace! {
+  /// Blocking `net` namespace
+  Net => Net {
+    /// Returns protocol version
+    fn version() -> String;
+    /// Returns number of peers connected to node.
+    fn peer_count() -> String;
+    /// Whether the node is listening for network connections
+    fn is_listening() -> bool;
+  }
+}
+
+blocking_namespace! {
+  /// Blocking `web3` namespace
+  Web3Api => Web3Api {
+    /// Returns client version
+    fn client_version() -> String;
+    /// Returns sha3 of the given data
+    fn sha3(bytes: Bytes) -> H256;
+  }
+}
+
+blocking_namespace! {
+  /// Blocking `personal` namespace
+  Personal => Personal {
+    /// Returns a list of available accounts.
+    fn list_accounts() -> Vec<Address>;
+    /// Creates a new account and protects it with given password.
+    fn new_account(password: &str) -> Address;
+    /// Unlocks the account with given password for some period of time (or single transaction).
+    fn unlock_account(address: Address, password: &str, duration: Option<u16>) -> bool;
+    /// Sends a transaction from locked account.
+    fn send_transaction(transaction: TransactionRequest, password: &str) -> H256;
+  }
+}
+
+/// Blocking contract interface
+#[derive(Debug)]
+pub struct Contract<T: Transport> {
+    contract: contract::Contract<T>,
+    runtime: Runtime,
+}
+
+impl<T: Transport> Contract<T> {
+    /// Borrows the asynchronous contract interface.
+    pub fn inner(&self) -> &contract::Contract<T> {
+        &self.contract
+    }
+
+    /// Returns contract address
+    pub fn address(&self) -> Address {
+        self.contract.address()
+    }
+
+    /// Execute a contract function
+    pub fn call<P>(&self, func: &str, params: P, from: Address, options: Options) -> Result<H256, contract::Error>
+    where
+        P: Tokenize,
+    {
+        self.runtime
+            .wait(self.contract.call(func, params, from, options))
+    }
+
+    /// Estimate gas required for this function call.
+    pub fn estimate_gas<P>(&self, func: &str, params: P, from: Address, options: Options) -> Result<U256, contract::Error>
+    where
+        P: Tokenize,
+    {
+        self.runtime
+            .wait(self.contract.estimate_gas(func, params, from, options))
+    }
+
+    /// Call constant function
+    pub fn query<R, A, B, P>(&self, func: &str, params: P, from: A, options: Options, block: B) -> Result<R, contract::Error>
+    where
+        R: Detokenize,
+        A: Into<Option<Address>>,
+        B: Into<Option<BlockNumber>>,
+        P: Tokenize,
+    {
+        self.runtime
+            .wait(self.contract.query(func, params, from, options, block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use futures::{self, Future};
+    use rpc::Value;
+    use helpers::tests::TestTransport;
+    use transports::MAX_REQUEST_TIMEOUT_SECS;
+    use types::U256;
+    use {Error, ErrorKind};
+    use super::Web3;
+
+    #[test]
+    fn should_block_on_namespace_calls() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let (height, peers) = {
+            let web3 = Web3::new(&transport);
+
+            // when
+            (web3.cita().block_number(), web3.net().peer_count())
+        };
+
+        // then
+        assert_eq!(height, Ok(U256::from(0x10)));
+        assert_eq!(peers, Ok("0x2".to_owned()));
+        transport.assert_request("cita_blockNumber", &[]);
+        transport.assert_request("net_peerCount", &[]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_time_out() {
+        // given
+        let transport = TestTransport::default();
+        let web3 = Web3::new(&transport).with_timeout(Duration::from_millis(200));
+
+        // when
+        let result = web3.wait(futures::empty::<(), Error>());
+
+        // then
+        assert_eq!(result, Err(ErrorKind::Timeout.into()));
+    }
+
+    #[test]
+    fn should_wait_with_timeouts_up_to_the_maximum() {
+        // given
+        let transport = TestTransport::default();
+        let web3 = Web3::new(&transport).with_timeout(Duration::from_secs(24 * 60 * 60));
+        let (tx, rx) = futures::oneshot::<()>();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.send(()).unwrap();
+        });
+
+        // when
+        let result = web3.wait(rx.map_err(|_| Error::from(ErrorKind::Internal)));
+
+        // then
+        assert_eq!(web3.timeout(), Duration::from_secs(MAX_REQUEST_TIMEOUT_SECS));
+        assert_eq!(result, Ok(()));
+    }
+}
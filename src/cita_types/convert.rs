@@ -0,0 +1,681 @@
+//! Lossless conversions between `types` and cita-common (`util`, `jsonrpc_types`) values.
+//!
+//! `PrivKey` and `PubKey` of the secp256k1 backend are `util::H256` and `util::H512`,
+//! so they convert from and into `types::H256` and `types::H512`.
+//!
+//! Blocks, transactions, receipts and logs of `jsonrpc_types` convert into their `types`
+//! counterparts through the JSON-RPC representation, which (unlike the Rust definitions)
+//! is the same across cita releases. The conversions fail with `ConversionError`
+//! on missing or malformed fields; fields CITA doesn't have are left at their defaults.
+//! `CitaTransaction` and `CitaReceipt` keep the fields Ethereum types can't represent.
+//!
+//! Submodules are `#[serde(with = "...")]` helpers serializing `util` values
+//! exactly like their `types` counterparts:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Account {
+//!     #[serde(with = "web3::cita_types::convert::address")]
+//!     address: util::Address,
+//! }
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use jsonrpc_types::rpctypes::{self, Data, Data20, Data32, Quantity};
+use libproto::blockchain::{SignedTransaction, UnverifiedTransaction};
+use protobuf;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
+use util::{self, Hashable};
+use types::{Block, Bytes, Index, Log, Transaction, TransactionReceipt, H160, H256, H512, U256, U64};
+
+macro_rules! hash_conversions {
+  ($name: ident, $rpc: ident) => {
+    impl From<util::$name> for $name {
+      fn from(hash: util::$name) -> Self {
+        $name::from(&hash[..])
+      }
+    }
+
+    impl From<$name> for util::$name {
+      fn from(hash: $name) -> Self {
+        util::$name::from_slice(&hash.0)
+      }
+    }
+
+    hash_conversions!(rpc => $name, $rpc);
+  };
+
+  (rpc => $name: ident, $rpc: ident) => {
+    impl From<$rpc> for $name {
+      fn from(hash: $rpc) -> Self {
+        let hash: util::$name = hash.into();
+        hash.into()
+      }
+    }
+
+    impl From<$name> for $rpc {
+      fn from(hash: $name) -> Self {
+        $rpc::new(hash.into())
+      }
+    }
+  };
+}
+
+hash_conversions!(H160, Data20);
+hash_conversions!(H256, Data32);
+
+impl From<util::H512> for H512 {
+    fn from(hash: util::H512) -> Self {
+        H512::from(&hash[..])
+    }
+}
+
+impl From<H512> for util::H512 {
+    fn from(hash: H512) -> Self {
+        util::H512::from_slice(&hash.0)
+    }
+}
+
+impl From<util::U256> for U256 {
+    fn from(value: util::U256) -> Self {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        U256(bytes)
+    }
+}
+
+impl From<U256> for util::U256 {
+    fn from(value: U256) -> Self {
+        util::U256::from_big_endian(&value.0)
+    }
+}
+
+impl From<Quantity> for U256 {
+    fn from(value: Quantity) -> Self {
+        let value: util::U256 = value.into();
+        value.into()
+    }
+}
+
+impl From<U256> for Quantity {
+    fn from(value: U256) -> Self {
+        Quantity::new(value.into())
+    }
+}
+
+// `Data` into `Bytes` is covered by `impl<T: Into<Vec<u8>>> From<T> for Bytes`.
+impl From<Bytes> for Data {
+    fn from(bytes: Bytes) -> Self {
+        Data::new(bytes.0)
+    }
+}
+
+/// Error converting `jsonrpc_types` values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value couldn't be serialized to JSON.
+    Json(String),
+    /// Required field is missing or `null`.
+    MissingField(&'static str),
+    /// Field has invalid format.
+    InvalidField(&'static str, String),
+    /// `content` of the transaction is not a valid `UnverifiedTransaction`.
+    InvalidContent(String),
+    /// Signature of the transaction can't be verified, so the sender is unknown.
+    InvalidSignature,
+    /// Nonce is not a decimal `u64` (CITA nonces are arbitrary strings, see `CitaTransaction`).
+    InvalidNonce(String),
+    /// Value of the transaction is longer than 32 bytes.
+    ValueTooLong(usize),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConversionError::Json(ref err) => write!(f, "Serialization error: {}", err),
+            ConversionError::MissingField(field) => write!(f, "Missing field: {}", field),
+            ConversionError::InvalidField(field, ref err) => write!(f, "Invalid field {}: {}", field, err),
+            ConversionError::InvalidContent(ref err) => write!(f, "Invalid transaction content: {}", err),
+            ConversionError::InvalidSignature => write!(f, "Invalid transaction signature"),
+            ConversionError::InvalidNonce(ref nonce) => write!(f, "Nonce is not a number: {}", nonce),
+            ConversionError::ValueTooLong(len) => write!(f, "Value has {} bytes, at most 32 are supported", len),
+        }
+    }
+}
+
+/// Transaction decoded from CITA, including the fields Ethereum transactions don't have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CitaTransaction {
+    /// Hash
+    pub hash: H256,
+    /// Block hash (`None` when pending)
+    pub block_hash: Option<H256>,
+    /// Block number (`None` when pending)
+    pub block_number: Option<U256>,
+    /// Index within the block (`None` when pending)
+    pub index: Option<Index>,
+    /// Sender (recovered from the signature)
+    pub from: H160,
+    /// Recipient (`None` when contract creation)
+    pub to: Option<H160>,
+    /// Nonce, an arbitrary string chosen by the sender
+    pub nonce: String,
+    /// Quota
+    pub quota: U256,
+    /// Last block the transaction can be included in
+    pub valid_until_block: u64,
+    /// Transferred value
+    pub value: U256,
+    /// Data
+    pub data: Bytes,
+}
+
+/// Transaction receipt together with the reason of failure reported by CITA.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CitaReceipt {
+    /// Receipt, `status` is `0` if `error_message` is set.
+    pub receipt: TransactionReceipt,
+    /// Error message of failed execution
+    pub error_message: Option<String>,
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Value, ConversionError> {
+    serde_json::to_value(value).map_err(|err| ConversionError::Json(err.to_string()))
+}
+
+fn parse<T: DeserializeOwned>(value: &Value, field: &'static str) -> Result<T, ConversionError> {
+    serde_json::from_value(value.clone()).map_err(|err| ConversionError::InvalidField(field, err.to_string()))
+}
+
+/// Deserializes value at given JSON pointer, fails if it's missing or `null`.
+fn required<T: DeserializeOwned>(json: &Value, pointer: &'static str) -> Result<T, ConversionError> {
+    optional(json, pointer)?.ok_or(ConversionError::MissingField(pointer))
+}
+
+/// Deserializes value at given JSON pointer, missing and `null` values become `None`.
+fn optional<T: DeserializeOwned>(json: &Value, pointer: &'static str) -> Result<Option<T>, ConversionError> {
+    match json.pointer(pointer) {
+        None | Some(&Value::Null) => Ok(None),
+        Some(value) => parse(value, pointer).map(Some),
+    }
+}
+
+impl TryFrom<rpctypes::Block> for Block<H256> {
+    type Error = ConversionError;
+
+    fn try_from(block: rpctypes::Block) -> Result<Self, Self::Error> {
+        let json = to_json(&block)?;
+        let transactions = match json.pointer("/body/transactions") {
+            Some(&Value::Array(ref transactions)) => transactions
+                .iter()
+                .map(|tx| match *tx {
+                    Value::Object(_) => required(tx, "/hash"),
+                    _ => parse(tx, "/body/transactions"),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(&Value::Null) | None => return Err(ConversionError::MissingField("/body/transactions")),
+            Some(_) => return Err(ConversionError::InvalidField("/body/transactions", "expected an array".into())),
+        };
+
+        Ok(Block {
+            hash: Some(required(&json, "/hash")?),
+            parent_hash: required(&json, "/header/prevHash")?,
+            uncles_hash: Default::default(),
+            author: required(&json, "/header/proposer")?,
+            state_root: required(&json, "/header/stateRoot")?,
+            transactions_root: required(&json, "/header/transactionsRoot")?,
+            receipts_root: required(&json, "/header/receiptsRoot")?,
+            number: Some(required(&json, "/header/number")?),
+            gas_used: required(&json, "/header/gasUsed")?,
+            gas_limit: Default::default(),
+            extra_data: Default::default(),
+            logs_bloom: Default::default(),
+            timestamp: U256::from(required::<u64>(&json, "/header/timestamp")?),
+            difficulty: Default::default(),
+            total_difficulty: Default::default(),
+            seal_fields: vec![],
+            uncles: vec![],
+            transactions,
+            size: None,
+        })
+    }
+}
+
+impl TryFrom<rpctypes::RpcBlock> for Block<H256> {
+    type Error = ConversionError;
+
+    fn try_from(block: rpctypes::RpcBlock) -> Result<Self, Self::Error> {
+        Block::try_from(rpctypes::Block::from(block))
+    }
+}
+
+impl TryFrom<rpctypes::Log> for Log {
+    type Error = ConversionError;
+
+    fn try_from(log: rpctypes::Log) -> Result<Self, Self::Error> {
+        let json = to_json(&log)?;
+        Ok(Log {
+            address: required(&json, "/address")?,
+            topics: required(&json, "/topics")?,
+            data: required(&json, "/data")?,
+            block_hash: optional(&json, "/blockHash")?,
+            block_number: optional(&json, "/blockNumber")?,
+            transaction_hash: optional(&json, "/transactionHash")?,
+            transaction_index: optional(&json, "/transactionIndex")?,
+            log_index: optional(&json, "/logIndex")?,
+            transaction_log_index: optional(&json, "/transactionLogIndex")?,
+        })
+    }
+}
+
+impl TryFrom<rpctypes::Receipt> for CitaReceipt {
+    type Error = ConversionError;
+
+    fn try_from(receipt: rpctypes::Receipt) -> Result<Self, Self::Error> {
+        let json = to_json(&receipt)?;
+        let error_message: Option<String> = optional(&json, "/errorMessage")?;
+        let receipt = TransactionReceipt {
+            transaction_hash: required(&json, "/transactionHash")?,
+            transaction_index: required(&json, "/transactionIndex")?,
+            block_hash: required(&json, "/blockHash")?,
+            block_number: required(&json, "/blockNumber")?,
+            cumulative_gas_used: required(&json, "/cumulativeGasUsed")?,
+            gas_used: required(&json, "/gasUsed")?,
+            contract_address: optional(&json, "/contractAddress")?,
+            logs: required(&json, "/logs")?,
+            logs_bloom: required(&json, "/logsBloom")?,
+            status: Some(U64::from(if error_message.is_some() { 0 } else { 1 })),
+            root: optional(&json, "/root")?,
+        };
+
+        Ok(CitaReceipt { receipt, error_message })
+    }
+}
+
+impl TryFrom<rpctypes::Receipt> for TransactionReceipt {
+    type Error = ConversionError;
+
+    /// CITA reports failures with `errorMessage`, which becomes `status == 0`.
+    /// Use `CitaReceipt` to get the message.
+    fn try_from(receipt: rpctypes::Receipt) -> Result<Self, Self::Error> {
+        CitaReceipt::try_from(receipt).map(|receipt| receipt.receipt)
+    }
+}
+
+impl TryFrom<rpctypes::RpcTransaction> for CitaTransaction {
+    type Error = ConversionError;
+
+    /// Decodes the signed transaction in `content` and recovers its sender.
+    fn try_from(transaction: rpctypes::RpcTransaction) -> Result<Self, Self::Error> {
+        let json = to_json(&transaction)?;
+        let content: Bytes = required(&json, "/content")?;
+        let unverified = protobuf::parse_from_bytes::<UnverifiedTransaction>(&content.0)
+            .map_err(|err| ConversionError::InvalidContent(err.to_string()))?;
+        // The address is the tail of the public key hash, the hash algorithm depends on the enabled features.
+        let from = SignedTransaction::create(&unverified)
+            .map(|signed| H160::from(&signed.get_signer().crypt_hash()[12..]))
+            .map_err(|_| ConversionError::InvalidSignature)?;
+        let tx = unverified.get_transaction();
+        let to = tx.get_to().trim_start_matches("0x");
+        let to = if to.is_empty() {
+            None
+        } else {
+            let to = H160::from_hex_str(&format!("0x{}", to))
+                .map_err(|err| ConversionError::InvalidField("/content/transaction/to", format!("{:?}", err)))?;
+            Some(to)
+        };
+        if tx.get_value().len() > 32 {
+            return Err(ConversionError::ValueTooLong(tx.get_value().len()));
+        }
+
+        Ok(CitaTransaction {
+            hash: required(&json, "/hash")?,
+            block_hash: optional(&json, "/blockHash")?,
+            block_number: optional(&json, "/blockNumber")?,
+            index: optional(&json, "/index")?,
+            from,
+            to,
+            nonce: tx.get_nonce().into(),
+            quota: U256::from(tx.get_quota()),
+            valid_until_block: tx.get_valid_until_block(),
+            value: U256::from(tx.get_value()),
+            data: tx.get_data().into(),
+        })
+    }
+}
+
+impl TryFrom<CitaTransaction> for Transaction {
+    type Error = ConversionError;
+
+    /// Quota becomes `gas` and `gas_price` is zero. Fails if the nonce is not a decimal `u64`.
+    fn try_from(tx: CitaTransaction) -> Result<Self, Self::Error> {
+        let nonce = tx.nonce
+            .parse::<u64>()
+            .map_err(|_| ConversionError::InvalidNonce(tx.nonce.clone()))?;
+
+        Ok(Transaction {
+            hash: tx.hash,
+            nonce: U256::from(nonce),
+            block_hash: tx.block_hash,
+            block_number: tx.block_number,
+            transaction_index: tx.index,
+            from: tx.from,
+            to: tx.to,
+            value: tx.value,
+            gas_price: U256::default(),
+            gas: tx.quota,
+            input: tx.data,
+        })
+    }
+}
+
+impl TryFrom<rpctypes::RpcTransaction> for Transaction {
+    type Error = ConversionError;
+
+    fn try_from(transaction: rpctypes::RpcTransaction) -> Result<Self, Self::Error> {
+        CitaTransaction::try_from(transaction).and_then(Transaction::try_from)
+    }
+}
+
+macro_rules! serde_with {
+  ($module: ident, $doc: expr, $util: ty, $local: ident) => {
+    #[doc = $doc]
+    pub mod $module {
+      use serde::{Deserialize, Deserializer, Serialize, Serializer};
+      use util;
+      use types::$local;
+
+      /// Serializes the value like its `types` counterpart.
+      pub fn serialize<S: Serializer>(value: &$util, serializer: S) -> Result<S::Ok, S::Error> {
+        $local::from(value.clone()).serialize(serializer)
+      }
+
+      /// Deserializes the value from representation of its `types` counterpart.
+      pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<$util, D::Error> {
+        $local::deserialize(deserializer).map(Into::into)
+      }
+    }
+  };
+}
+
+serde_with!(address, "`util::Address` serialized as `types::Address`.", util::Address, H160);
+serde_with!(hash, "`util::H256` serialized as `types::H256`.", util::H256, H256);
+serde_with!(uint, "`util::U256` serialized as `types::U256`.", util::U256, U256);
+
+#[cfg(test)]
+mod tests {
+    use std::convert::{TryFrom, TryInto};
+    use cita_crypto::{CreateKey, KeyPair};
+    use jsonrpc_types::rpctypes::{self, Data, Data20, Quantity};
+    use libproto::blockchain::Transaction as ProtoTransaction;
+    use protobuf::core::Message;
+    use rustc_hex::ToHex;
+    use serde_json;
+    use util;
+    use types::{Block, Bytes, Log, Transaction, TransactionReceipt, H160, H256, U256, U64};
+    use super::{CitaReceipt, CitaTransaction, ConversionError};
+
+    const BLOCK_HASH: &str = "0x3a8d4e4bc0d1ae5a6a0dd7c5e8c4a1e9d5c0f9b0b4b5a2f6a1e1c5d0e6b7c8d9";
+    const TX_HASH: &str = "0x2a4c6f8e1b3d5f7092a4c6e8f1b3d5e7092b4c6d8f0a2b4c6e8f0a2c4e6f8a0b";
+
+    fn log_json() -> String {
+        format!(
+            r#"{{
+              "address": "0x35bd452c37d28beca42097cfd8ba671c8dd430a1",
+              "topics": ["0xe4af93ca7e370881e6f1b57272e42a3d851d3cc6d951b4f4d2e7a963914468a2"],
+              "data": "0x0000000000000000000000000000000000000000000000000000000000000064",
+              "blockHash": "{}",
+              "blockNumber": "0x1f",
+              "transactionHash": "{}",
+              "transactionIndex": "0x0",
+              "logIndex": "0x0",
+              "transactionLogIndex": "0x0"
+            }}"#,
+            BLOCK_HASH, TX_HASH
+        )
+    }
+
+    fn receipt_json(error: &str) -> String {
+        format!(
+            r#"{{
+              "transactionHash": "{tx}",
+              "transactionIndex": "0x0",
+              "blockHash": "{block}",
+              "blockNumber": "0x1f",
+              "cumulativeGasUsed": "0x4f2d",
+              "gasUsed": "0x4f2d",
+              "contractAddress": null,
+              "logs": [{log}],
+              "root": null,
+              "logsBloom": "0x{bloom}",
+              "errorMessage": {error}
+            }}"#,
+            tx = TX_HASH,
+            block = BLOCK_HASH,
+            log = log_json(),
+            bloom = "0".repeat(512),
+            error = error
+        )
+    }
+
+    fn transaction_json(content: &str) -> String {
+        format!(
+            r#"{{
+              "hash": "{tx}",
+              "content": "0x{content}",
+              "blockNumber": "0x1f",
+              "blockHash": "{block}",
+              "index": "0x0"
+            }}"#,
+            tx = TX_HASH,
+            content = content,
+            block = BLOCK_HASH
+        )
+    }
+
+    fn signed_transaction(keypair: &KeyPair, nonce: &str) -> rpctypes::RpcTransaction {
+        let mut tx = ProtoTransaction::new();
+        tx.set_to("35bd452c37d28beca42097cfd8ba671c8dd430a1".into());
+        tx.set_nonce(nonce.into());
+        tx.set_quota(1_000_000);
+        tx.set_valid_until_block(100);
+        tx.set_data(vec![0x60, 0x80]);
+        let content = tx.sign(*keypair.privkey())
+            .take_transaction_with_sig()
+            .write_to_bytes()
+            .unwrap();
+        serde_json::from_str(&transaction_json(&content.to_hex())).unwrap()
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Account {
+        #[serde(with = "super::address")]
+        address: util::Address,
+        #[serde(with = "super::uint")]
+        balance: util::U256,
+    }
+
+    #[test]
+    fn should_convert_hashes() {
+        let address = H160::from_hex_str("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+        let hash = H256::from(0x1234);
+
+        let cita_address: util::Address = address.into();
+        let cita_hash: util::H256 = hash.into();
+        let rpc_address: Data20 = address.into();
+
+        assert_eq!(&cita_address[..], &address[..]);
+        assert_eq!(H160::from(cita_address), address);
+        assert_eq!(H256::from(cita_hash), hash);
+        assert_eq!(H160::from(rpc_address), address);
+    }
+
+    #[test]
+    fn should_convert_uints_and_bytes() {
+        let value = U256::max_value() - U256::from(0x1234);
+
+        let cita_value: util::U256 = value.into();
+        let rpc_value: Quantity = value.into();
+        let data: Data = Bytes(vec![1, 2, 3]).into();
+
+        assert_eq!(cita_value, util::U256::max_value() - util::U256::from(0x1234u64));
+        assert_eq!(U256::from(cita_value), value);
+        assert_eq!(U256::from(rpc_value), value);
+        assert_eq!(Bytes::from(data), Bytes(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn should_serialize_cita_values_like_types() {
+        // given
+        let account = Account {
+            address: H160::from(0x10).into(),
+            balance: U256::from(0x20).into(),
+        };
+
+        // when
+        let json = serde_json::to_string(&account).unwrap();
+
+        // then
+        assert_eq!(
+            json,
+            r#"{"address":"0x0000000000000000000000000000000000000010","balance":"0x20"}"#
+        );
+        assert_eq!(serde_json::from_str::<Account>(&json).unwrap(), account);
+    }
+
+    #[test]
+    fn should_convert_cita_block() {
+        // given
+        let json = format!(
+            r#"{{
+              "version": 0,
+              "hash": "{block}",
+              "header": {{
+                "timestamp": 1528445473474,
+                "prevHash": "0x8f9d2ab4e8c1d0e4f9b3a5c7d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0",
+                "number": "0x1f",
+                "stateRoot": "0x4f8ecb0ab1e5a3c2d8b1e6f0a9c4d3b2a1e0f9d8c7b6a5f4e3d2c1b0a9f8e7d6",
+                "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "gasUsed": "0x4f2d",
+                "proof": {{
+                  "Tendermint": {{
+                    "proposal": "0x1e2d3c4b5a69788796a5b4c3d2e1f0091827364554637281900a1b2c3d4e5f60",
+                    "height": 30,
+                    "round": 0,
+                    "commits": {{}}
+                  }}
+                }},
+                "proposer": "0x2b7a0d8e1c4f3a6b9d2e5f8a1c4b7d0e3f6a9c2b"
+              }},
+              "body": {{
+                "transactions": ["{tx}"]
+              }}
+            }}"#,
+            block = BLOCK_HASH,
+            tx = TX_HASH
+        );
+        let block: rpctypes::Block = serde_json::from_str(&json).unwrap();
+
+        // when
+        let block: Block<H256> = block.try_into().unwrap();
+
+        // then
+        assert_eq!(block.hash, Some(H256::from_hex_str(BLOCK_HASH).unwrap()));
+        assert_eq!(
+            block.author,
+            H160::from_hex_str("0x2b7a0d8e1c4f3a6b9d2e5f8a1c4b7d0e3f6a9c2b").unwrap()
+        );
+        assert_eq!(block.number, Some(U64::from(0x1f)));
+        assert_eq!(block.gas_used, U256::from(0x4f2d));
+        assert_eq!(block.timestamp, U256::from(1_528_445_473_474u64));
+        assert_eq!(block.transactions, vec![H256::from_hex_str(TX_HASH).unwrap()]);
+    }
+
+    #[test]
+    fn should_convert_cita_receipt_and_logs() {
+        // given
+        let succeeded: rpctypes::Receipt = serde_json::from_str(&receipt_json("null")).unwrap();
+        let failed: rpctypes::Receipt = serde_json::from_str(&receipt_json(r#""Reverted.""#)).unwrap();
+        let log: rpctypes::Log = serde_json::from_str(&log_json()).unwrap();
+
+        // when
+        let succeeded: TransactionReceipt = succeeded.try_into().unwrap();
+        let failed = CitaReceipt::try_from(failed).unwrap();
+        let log: Log = log.try_into().unwrap();
+
+        // then
+        assert_eq!(succeeded.transaction_hash, H256::from_hex_str(TX_HASH).unwrap());
+        assert_eq!(succeeded.block_number, U256::from(0x1f));
+        assert_eq!(succeeded.gas_used, U256::from(0x4f2d));
+        assert_eq!(succeeded.logs, vec![log.clone()]);
+        assert!(!succeeded.is_failed());
+        assert!(failed.receipt.is_failed());
+        assert_eq!(failed.error_message, Some("Reverted.".into()));
+        assert_eq!(log.block_hash, Some(H256::from_hex_str(BLOCK_HASH).unwrap()));
+        assert_eq!(log.topics.len(), 1);
+        assert_eq!(log.data.0[31], 0x64);
+    }
+
+    #[test]
+    fn should_decode_cita_transaction() {
+        // given
+        let keypair = KeyPair::gen_keypair();
+        let transaction = signed_transaction(&keypair, "7");
+
+        // when
+        let transaction: Transaction = transaction.try_into().unwrap();
+
+        // then
+        assert_eq!(transaction.hash, H256::from_hex_str(TX_HASH).unwrap());
+        assert_eq!(transaction.block_number, Some(U256::from(0x1f)));
+        assert_eq!(transaction.from, H160::from(keypair.address()));
+        assert_eq!(
+            transaction.to,
+            Some(H160::from_hex_str("0x35bd452c37d28beca42097cfd8ba671c8dd430a1").unwrap())
+        );
+        assert_eq!(transaction.nonce, U256::from(7));
+        assert_eq!(transaction.gas, U256::from(1_000_000));
+        assert_eq!(transaction.input, Bytes(vec![0x60, 0x80]));
+    }
+
+    #[test]
+    fn should_keep_string_nonce_of_cita_transaction() {
+        // given
+        let keypair = KeyPair::gen_keypair();
+        let transaction = signed_transaction(&keypair, "a8f2c1e0-nonce");
+
+        // when
+        let cita = CitaTransaction::try_from(transaction.clone()).unwrap();
+        let result = Transaction::try_from(transaction);
+
+        // then
+        assert_eq!(cita.nonce, "a8f2c1e0-nonce");
+        assert_eq!(cita.valid_until_block, 100);
+        assert_eq!(cita.from, H160::from(keypair.address()));
+        assert_eq!(result, Err(ConversionError::InvalidNonce("a8f2c1e0-nonce".into())));
+    }
+
+    #[test]
+    fn should_reject_malformed_cita_values() {
+        // given
+        let content: rpctypes::RpcTransaction = serde_json::from_str(&transaction_json("ffff")).unwrap();
+        let mut receipt: serde_json::Value = serde_json::from_str(&receipt_json("null")).unwrap();
+        receipt["blockHash"] = serde_json::Value::Null;
+        let receipt: rpctypes::Receipt = serde_json::from_value(receipt).unwrap();
+
+        // when
+        let content = Transaction::try_from(content);
+        let receipt = TransactionReceipt::try_from(receipt);
+
+        // then
+        match content {
+            Err(ConversionError::InvalidContent(_)) => {}
+            other => panic!("Expected invalid content, got: {:?}", other),
+        }
+        assert_eq!(receipt, Err(ConversionError::MissingField("/blockHash")));
+    }
+}
@@ -2,6 +2,7 @@
 mod call_request;
 mod account;
 mod error;
+pub mod convert;
 pub use libproto::TxResponse;
 pub use libproto::blockchain::{Crypto, SignedTransaction, Transaction, UnverifiedTransaction};
 pub use jsonrpc_types::rpctypes::{transaction, Block, BlockTransaction, FilterChanges, Log, Receipt, RpcBlock,
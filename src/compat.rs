@@ -0,0 +1,140 @@
+//! `std::future::Future` adapters for async/await.
+//!
+//! Every future and stream of this crate (namespaces, `Contract`, `confirm`, filter streams)
+//! can be awaited after converting it with `compat()`:
+//!
+//! ```ignore
+//! use web3::compat::{AsyncTransport, FutureExt, StreamExt};
+//!
+//! let height = web3.cita().block_number().compat().await?;
+//! let peers = transport.execute_async("net_peerCount", vec![]).await?;
+//! let blocks = filter.stream(Duration::from_secs(1)).compat();
+//! ```
+//!
+//! Transports keep driving requests on their own event loop threads (see `EventLoopHandle`),
+//! so the adapters can be awaited on any executor, including a current tokio runtime.
+
+use rpc;
+use {BatchTransport, RequestId, Transport};
+
+pub use futures03::compat::{Compat01As03 as Compat, Future01CompatExt as FutureExt, Stream01CompatExt as StreamExt};
+
+/// Transport returning `std::future::Future`s.
+pub trait AsyncTransport: Transport {
+    /// Execute remote method with given parameters.
+    fn execute_async(&self, method: &str, params: Vec<rpc::Value>) -> Compat<Self::Out> {
+        Compat::new(self.execute(method, params))
+    }
+
+    /// Execute prepared RPC call.
+    fn send_async(&self, id: RequestId, request: rpc::Call) -> Compat<Self::Out> {
+        Compat::new(self.send(id, request))
+    }
+}
+
+impl<T: Transport> AsyncTransport for T {}
+
+/// Batch transport returning `std::future::Future`s.
+pub trait AsyncBatchTransport: BatchTransport {
+    /// Sends a batch of prepared RPC calls.
+    fn send_batch_async<I>(&self, requests: I) -> Compat<Self::Batch>
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        Compat::new(self.send_batch(requests))
+    }
+}
+
+impl<T: BatchTransport> AsyncBatchTransport for T {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use futures03::executor::block_on;
+    use futures03::stream::StreamExt as StdStreamExt;
+    use futures::stream;
+    use rpc::Value;
+    use api::{Cita, EthFilter, Namespace};
+    use helpers::tests::TestTransport;
+    use types::{H256, U256};
+    use {Error, ErrorKind, Transport};
+    use super::{AsyncBatchTransport, AsyncTransport, FutureExt, StreamExt};
+
+    #[test]
+    fn should_await_namespace_calls() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        transport.add_response(Value::String("0x2".into()));
+
+        // when
+        let height = block_on(Cita::new(&transport).block_number().compat());
+        let peers = block_on(transport.execute_async("net_peerCount", vec![]));
+        let failed = block_on(transport.execute_async("net_peerCount", vec![]));
+
+        // then
+        assert_eq!(height, Ok(U256::from(0x10)));
+        assert_eq!(peers, Ok(Value::String("0x2".into())));
+        assert_eq!(failed, Err(ErrorKind::Unreachable.into()));
+        transport.assert_request("cita_blockNumber", &[]);
+        transport.assert_request("net_peerCount", &[]);
+        transport.assert_request("net_peerCount", &[]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_await_batches() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let requests = vec![
+            transport.prepare("net_peerCount", vec![]),
+            transport.prepare("cita_blockNumber", vec![]),
+        ];
+
+        // when
+        let results = block_on(transport.send_batch_async(requests));
+
+        // then
+        assert_eq!(
+            results,
+            Ok(vec![Ok(Value::String("0x1".into())), Ok(Value::String("0x2".into()))])
+        );
+    }
+
+    #[test]
+    fn should_adapt_filter_streams() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        transport.add_response(Value::Array(vec![
+            Value::String(r#"0x0000000000000000000000000000000000000000000000000000000000000456"#.into()),
+        ]));
+        transport.add_response(Value::Array(vec![
+            Value::String(r#"0x0000000000000000000000000000000000000000000000000000000000000457"#.into()),
+        ]));
+        let result = {
+            let eth = EthFilter::new(&transport);
+
+            // when
+            let filter = block_on(eth.create_blocks_filter().compat()).unwrap();
+            block_on(filter.stream(Duration::from_secs(0)).compat().take(2).collect::<Vec<_>>())
+        };
+
+        // then
+        assert_eq!(result, vec![Ok(H256::from(0x456)), Ok(H256::from(0x457))]);
+        transport.assert_request("eth_newBlockFilter", &[]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+        transport.assert_request("eth_getFilterChanges", &[r#""0x123""#.into()]);
+    }
+
+    #[test]
+    fn should_adapt_streams() {
+        let items = stream::iter_ok::<_, Error>(vec![1, 2]).compat();
+
+        let items = block_on(items.collect::<Vec<_>>());
+
+        assert_eq!(items, vec![Ok(1), Ok(2)]);
+    }
+}
@@ -6,7 +6,7 @@ use futures::stream::Skip;
 use api::{CreateFilter, Eth, EthFilter, FilterStream, Namespace};
 use types::{H256, TransactionReceipt, TransactionRequest, U256};
 use helpers::CallResult;
-use {Error, Transport};
+use {Error, ErrorKind, Transport};
 
 /// Checks whether an event has been confirmed.
 pub trait ConfirmationCheck {
@@ -262,6 +262,9 @@ impl<T: Transport> Future for SendTransactionWithConfirmation<T> {
                 SendTransactionWithConfirmationState::GetTransactionReceipt(ref mut future) => {
                     let receipt = try_ready!(Future::poll(future))
                         .expect("receipt can't be null after wait for confirmations; qed");
+                    if receipt.is_failed() {
+                        return Err(ErrorKind::TransactionFailed(receipt.transaction_hash).into());
+                    }
                     return Ok(receipt.into());
                 }
             };
@@ -271,6 +274,8 @@ impl<T: Transport> Future for SendTransactionWithConfirmation<T> {
 }
 
 /// Sends transaction and returns future resolved after transaction is confirmed
+///
+/// Fails with `ErrorKind::TransactionFailed` if the receipt reports `status == 0`.
 pub fn send_transaction_with_confirmation<T>(
     transport: T,
     tx: TransactionRequest,
@@ -291,6 +296,7 @@ mod tests {
     use types::{TransactionReceipt, TransactionRequest};
     use super::send_transaction_with_confirmation;
     use rpc::Value;
+    use ErrorKind;
 
     #[test]
     fn test_send_transaction_with_confirmation() {
@@ -316,6 +322,9 @@ mod tests {
             gas_used: 0.into(),
             contract_address: None,
             logs: vec![],
+            logs_bloom: Default::default(),
+            status: Some(1.into()),
+            root: None,
         };
 
         let poll_interval = Duration::from_secs(0);
@@ -388,4 +397,47 @@ mod tests {
         transport.assert_no_more_requests();
         assert_eq!(confirmation, Ok(transaction_receipt));
     }
+
+    #[test]
+    fn should_fail_if_transaction_reverted() {
+        // given
+        let mut transport = TestTransport::default();
+        let transaction_request = TransactionRequest {
+            from: 0x123.into(),
+            to: Some(0x123.into()),
+            gas: None,
+            gas_price: None,
+            value: None,
+            data: None,
+            nonce: None,
+            condition: None,
+        };
+        let transaction_receipt = TransactionReceipt {
+            transaction_hash: 0x111.into(),
+            block_number: 2.into(),
+            status: Some(0.into()),
+            ..Default::default()
+        };
+        transport.add_response(Value::String(
+            r#"0x0000000000000000000000000000000000000000000000000000000000000111"#.into(),
+        ));
+        transport.add_response(Value::String("0x123".into()));
+        transport.add_response(Value::Array(vec![
+            Value::String(r#"0x0000000000000000000000000000000000000000000000000000000000000456"#.into()),
+        ]));
+        transport.add_response(json!(transaction_receipt));
+        transport.add_response(Value::String("0x2".into()));
+        transport.add_response(json!(transaction_receipt));
+
+        // when
+        let confirmation = send_transaction_with_confirmation(
+            &transport,
+            transaction_request,
+            Duration::from_secs(0),
+            0,
+        ).wait();
+
+        // then
+        assert_eq!(confirmation, Err(ErrorKind::TransactionFailed(0x111.into()).into()));
+    }
 }
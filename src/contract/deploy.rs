@@ -99,7 +99,14 @@ impl<T: Transport> Future for PendingContract<T> {
     type Error = Error;
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-        let receipt = try_ready!(self.waiting.poll());
+        let receipt = match self.waiting.poll() {
+            Ok(Async::Ready(receipt)) => receipt,
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Err(::Error(::ErrorKind::TransactionFailed(hash), _)) => {
+                return Err(ErrorKind::ContractDeploymentFailure(hash).into())
+            }
+            Err(err) => return Err(err.into()),
+        };
         let eth = self.eth.take().expect("future polled after ready; qed");
         let abi = self.abi.take().expect("future polled after ready; qed");
 
@@ -116,8 +123,9 @@ mod tests {
     use futures::Future;
     use helpers::tests::TestTransport;
     use rpc;
-    use types::U256;
+    use types::{TransactionReceipt, U256};
     use contract::{Contract, Options};
+    use super::ErrorKind;
 
     #[test]
     fn should_deploy_a_contract() {
@@ -195,4 +203,49 @@ mod tests {
         );
         transport.assert_no_more_requests();
     }
+
+    #[test]
+    fn should_fail_deployment_if_transaction_reverted() {
+        // given
+        let mut transport = TestTransport::default();
+        let receipt = TransactionReceipt {
+            transaction_hash: 0x111.into(),
+            block_number: 2.into(),
+            contract_address: Some(5.into()),
+            status: Some(0.into()),
+            ..Default::default()
+        };
+        transport.add_response(rpc::Value::String(
+            "0x0000000000000000000000000000000000000000000000000000000000000111".into(),
+        ));
+        transport.add_response(rpc::Value::String("0x0".into()));
+        transport.add_response(rpc::Value::Array(vec![rpc::Value::String(
+            "0xd5311584a9867d8e129113e1ec9db342771b94bd4533aeab820a5bcc2c54878f".into(),
+        )]));
+        transport.add_response(json!(receipt));
+        transport.add_response(rpc::Value::String("0x2".into()));
+        transport.add_response(json!(receipt));
+
+        // when
+        let result = Contract::deploy(api::Eth::new(&transport), include_bytes!("./res/token.json"))
+            .unwrap()
+            .confirmations(0)
+            .poll_interval(::std::time::Duration::from_secs(0))
+            .execute(
+                vec![1, 2, 3, 4],
+                (U256::from(1_000_000), "My Token".to_owned(), 3u64, "MT".to_owned()),
+                5.into(),
+            )
+            .unwrap()
+            .wait();
+
+        // then
+        match result {
+            Err(ref err) => match *err.kind() {
+                ErrorKind::ContractDeploymentFailure(hash) => assert_eq!(hash, 0x111.into()),
+                ref kind => panic!("Unexpected error: {:?}", kind),
+            },
+            Ok(_) => panic!("Expected deployment failure"),
+        }
+    }
 }
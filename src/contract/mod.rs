@@ -5,7 +5,8 @@ use ethabi;
 use std::time;
 use api::Eth;
 use contract::tokens::{Detokenize, Tokenize};
-use types::{Address, BlockNumber, Bytes, CallRequest, H256, TransactionCondition, TransactionRequest, U256};
+use types::{Address, BlockNumber, Bytes, CallRequest, FilterBuilder, H256, TransactionCondition, TransactionRequest,
+            U256};
 use Transport;
 
 mod error;
@@ -82,6 +83,16 @@ impl<T: Transport> Contract<T> {
         self.address
     }
 
+    /// Creates logs filter of given event, scoped to this contract.
+    ///
+    /// Use `FilterBuilder::indexed` to filter by indexed parameters.
+    pub fn event_filter(&self, event: &str) -> Result<FilterBuilder, ethabi::Error> {
+        let event = self.abi.event(event.into())?;
+        Ok(FilterBuilder::default()
+            .address(vec![self.address])
+            .event(event))
+    }
+
     /// Execute a contract function
     pub fn call<P>(&self, func: &str, params: P, from: Address, options: Options) -> CallResult<H256, T::Out>
     where
@@ -330,4 +341,36 @@ mod tests {
         transport.assert_no_more_requests();
         assert_eq!(result, 0x20.into());
     }
+
+    #[test]
+    fn should_create_event_filter() {
+        // given
+        let transport = TestTransport::default();
+        let token = contract(&transport);
+
+        // when
+        let filter = token
+            .event_filter("Transfer")
+            .unwrap()
+            .indexed(1, vec![Address::from(5)])
+            .build();
+
+        // then
+        assert_eq!(
+            ::serde_json::to_value(&filter).unwrap(),
+            json!({
+                "fromBlock": null,
+                "toBlock": null,
+                "address": ["0x0000000000000000000000000000000000000001"],
+                "topics": [
+                    ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+                    null,
+                    ["0x0000000000000000000000000000000000000000000000000000000000000005"],
+                    null
+                ],
+                "limit": null
+            })
+        );
+        assert!(token.event_filter("Approval").is_err());
+    }
 }
@@ -3,7 +3,7 @@
 use arrayvec::ArrayVec;
 use ethabi::Token;
 use contract::error::{Error, ErrorKind};
-use types::{self, Address, H256, U256, U64};
+use types::{self, Address, Amount, Unit, H256, U256, U64};
 
 /// Output type possible to deserialize from Contract ABI
 pub trait Detokenize {
@@ -198,6 +198,18 @@ macro_rules! uint_tokenizable {
 uint_tokenizable!(U256, "U256");
 uint_tokenizable!(U64, "U64");
 
+/// Decoding loses the decimals: the token holds only base units, so decoded amounts have no decimals
+/// (`Unit::Wei`). Use `Amount::with_decimals` to restore the decimals of the token.
+impl Tokenizable for Amount {
+    fn from_token(token: Token) -> Result<Self, Error> {
+        U256::from_token(token).map(|value| Amount::new(value, Unit::Wei))
+    }
+
+    fn into_token(self) -> Token {
+        self.value().into_token()
+    }
+}
+
 impl Tokenizable for u64 {
     fn from_token(token: Token) -> Result<Self, Error> {
         match token {
@@ -323,7 +335,7 @@ impl_fixed_types!(1024);
 mod tests {
     use ethabi::Token;
     use super::Detokenize;
-    use types::{Address, U256};
+    use types::{Address, Amount, U256};
 
     fn output<R: Detokenize>() -> R {
         unimplemented!()
@@ -334,6 +346,7 @@ mod tests {
     fn should_be_able_to_compile() {
         let _tokens: Vec<Token> = output();
         let _uint: U256 = output();
+        let _amount: Amount = output();
         let _address: Address = output();
         let _string: String = output();
         let _bool: bool = output();
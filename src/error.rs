@@ -6,6 +6,7 @@
 use std::io;
 use serde_json;
 use rpc;
+use types::H256;
 
 error_chain! {
   foreign_links {
@@ -37,6 +38,14 @@ error_chain! {
       description("web3 internal error"),
       display("Internal Web3 error"),
     }
+    Timeout {
+      description("request timed out"),
+      display("Request timed out"),
+    }
+    TransactionFailed(hash: H256) {
+      description("transaction failed"),
+      display("Transaction failed with status 0. Tx hash: {:?}", hash),
+    }
   }
 }
 
@@ -56,6 +65,8 @@ impl Clone for Error {
             ErrorKind::Transport(ref t) => ErrorKind::Transport(t.to_owned()),
             ErrorKind::Rpc(ref e) => ErrorKind::Rpc(e.clone()),
             ErrorKind::Internal => ErrorKind::Internal,
+            ErrorKind::Timeout => ErrorKind::Timeout,
+            ErrorKind::TransactionFailed(hash) => ErrorKind::TransactionFailed(hash),
             ErrorKind::Msg(ref e) => ErrorKind::Msg(e.clone()).into(),
             _ => unimplemented!(),
         }.into()
@@ -82,6 +93,8 @@ impl PartialEq for ErrorKind {
             (&Transport(ref a), &Transport(ref b)) => a == b,
             (&Rpc(ref a), &Rpc(ref b)) => a == b,
             (&Internal, &Internal) => true,
+            (&Timeout, &Timeout) => true,
+            (&TransactionFailed(ref a), &TransactionFailed(ref b)) => a == b,
             (&Msg(ref a), &Msg(ref b)) => a == b,
             _ => false,
         }
@@ -25,6 +25,11 @@ impl<T, F> CallResult<T, F> {
             _marker: PhantomData,
         }
     }
+
+    /// Returns the wrapped future.
+    pub(crate) fn inner(&self) -> &F {
+        &self.inner
+    }
 }
 
 impl<T: serde::de::DeserializeOwned, F> Future for CallResult<T, F>
@@ -55,6 +60,17 @@ pub fn to_string<T: serde::Serialize>(request: &T) -> String {
     serde_json::to_string(&request).expect("String serialization never fails.")
 }
 
+/// Serialize RPC parameters.
+/// Tuples and sequences become positional parameters (use a 1-tuple to pass a single array),
+/// `()` means no parameters and any other value is passed as the only parameter.
+pub fn to_params<T: serde::Serialize>(params: &T) -> Vec<rpc::Value> {
+    match serialize(params) {
+        rpc::Value::Null => vec![],
+        rpc::Value::Array(params) => params,
+        param => vec![param],
+    }
+}
+
 /// Build a JSON-RPC request.
 pub fn build_request(id: usize, method: &str, params: Vec<rpc::Value>) -> rpc::Call {
     rpc::Call::MethodCall(rpc::MethodCall {
@@ -83,6 +99,60 @@ pub fn to_result_from_output(output: rpc::Output) -> Result<rpc::Value, Error> {
     }
 }
 
+/// Defines a namespace type with typed methods.
+///
+/// ```ignore
+/// web3_namespace! {
+///   /// `txpool` namespace
+///   TxPool {
+///     /// Returns number of pending and queued transactions
+///     fn status() -> TxPoolStatus = "txpool_status";
+///     /// Returns pending transactions of given account
+///     fn content_from(address: Address) -> Vec<Transaction> = "txpool_contentFrom";
+///   }
+/// }
+///
+/// let status = web3.api::<TxPool<_>>().status();
+/// ```
+#[macro_export]
+macro_rules! web3_namespace {
+  (
+    $(#[$attr: meta])*
+    $name: ident {
+      $(
+        $(#[$method_attr: meta])*
+        fn $method: ident ( $($param: ident : $param_ty: ty),* ) -> $ret: ty = $rpc: expr;
+      )*
+    }
+  ) => {
+    $(#[$attr])*
+    #[derive(Debug, Clone)]
+    pub struct $name<T> {
+      transport: T,
+    }
+
+    impl<T: $crate::Transport> $crate::api::Namespace<T> for $name<T> {
+      fn new(transport: T) -> Self {
+        $name { transport }
+      }
+
+      fn transport(&self) -> &T {
+        &self.transport
+      }
+    }
+
+    impl<T: $crate::Transport> $name<T> {
+      $(
+        $(#[$method_attr])*
+        pub fn $method(&self, $($param: $param_ty),*) -> $crate::helpers::CallResult<$ret, T::Out> {
+          let params = vec![$($crate::helpers::serialize(&$param)),*];
+          $crate::helpers::CallResult::new($crate::Transport::execute(&self.transport, $rpc, params))
+        }
+      )*
+    }
+  };
+}
+
 #[macro_use]
 #[cfg(test)]
 pub mod tests {
@@ -91,7 +161,7 @@ pub mod tests {
     use std::collections::VecDeque;
     use futures;
     use rpc;
-    use {ErrorKind, RequestId, Result, Transport};
+    use {BatchTransport, Error, ErrorKind, RequestId, Result, Transport};
 
     #[derive(Debug, Default, Clone)]
     pub struct TestTransport {
@@ -120,6 +190,23 @@ pub mod tests {
         }
     }
 
+    impl BatchTransport for TestTransport {
+        type Batch = Result<Vec<::std::result::Result<rpc::Value, Error>>>;
+
+        fn send_batch<T>(&self, requests: T) -> Self::Batch
+        where
+            T: IntoIterator<Item = (RequestId, rpc::Call)>,
+        {
+            use futures::Future;
+
+            let results = requests
+                .into_iter()
+                .map(|(id, request)| self.send(id, request).wait())
+                .collect();
+            Box::new(futures::finished(results))
+        }
+    }
+
     impl TestTransport {
         pub fn set_response(&mut self, value: rpc::Value) {
             *self.response.borrow_mut() = vec![value].into();
@@ -17,9 +17,13 @@ extern crate serde;
 extern crate serde_derive;
 #[cfg_attr(test, macro_use)]
 extern crate serde_json;
+extern crate tiny_keccak;
 extern crate tokio_timer;
 extern crate util;
 
+#[cfg(feature = "std-future")]
+extern crate futures03;
+
 extern crate jsonrpc_types;
 extern crate libproto;
 
@@ -32,12 +36,15 @@ pub extern crate futures;
 #[macro_use]
 pub mod helpers;
 pub mod api;
+pub mod blocking;
 pub mod contract;
 pub mod error;
 pub mod transports;
 pub mod types;
 pub mod confirm;
 pub mod cita_types;
+#[cfg(feature = "std-future")]
+pub mod compat;
 
 use futures::Future;
 
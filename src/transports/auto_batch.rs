@@ -0,0 +1,437 @@
+//! Auto-flushing Batching Transport
+//!
+//! Collects calls into batches which are sent automatically,
+//! either when the batch is full or when the first call in it waited long enough.
+
+use std::fmt;
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Poll};
+use futures::task::AtomicTask;
+use futures::future::Shared;
+use parking_lot::Mutex;
+use rpc;
+use tokio_timer::{self, Sleep, Timer};
+use {BatchTransport, Error, ErrorKind, RequestId, Transport};
+
+/// Default maximal number of calls in a single batch.
+pub const DEFAULT_MAX_SIZE: usize = 100;
+/// Default time a call waits for other calls to join the batch.
+pub const DEFAULT_LINGER_MS: u64 = 10;
+
+// Slots of the dedicated timer wheel, ticks are made long enough for the wheel to cover the linger time.
+const TIMER_SLOTS: usize = 4096;
+
+/// A batch which is either being collected or was already sent.
+struct Slot<F: Future> {
+    sent: Option<Shared<F>>,
+    waiting: Vec<Arc<AtomicTask>>,
+}
+
+type SharedSlot<F> = Arc<Mutex<Slot<F>>>;
+
+/// Batch being collected.
+struct Queue<F: Future> {
+    requests: Vec<(RequestId, rpc::Call)>,
+    deadline: Instant,
+    slot: SharedSlot<F>,
+}
+
+impl<F: Future> Queue<F> {
+    fn new(linger: Duration) -> Self {
+        Queue {
+            requests: vec![],
+            deadline: Instant::now() + linger,
+            slot: Arc::new(Mutex::new(Slot {
+                sent: None,
+                waiting: vec![],
+            })),
+        }
+    }
+}
+
+struct Inner<T: BatchTransport> {
+    transport: T,
+    max_size: usize,
+    linger: Duration,
+    queue: Mutex<Queue<T::Batch>>,
+    timer: Timer,
+}
+
+impl<T: BatchTransport> Inner<T> {
+    /// Sends the batch occupying given slot (unless it was sent already).
+    fn flush(&self, slot: &SharedSlot<T::Batch>) {
+        let requests = {
+            let mut queue = self.queue.lock();
+            if !Arc::ptr_eq(&queue.slot, slot) {
+                return;
+            }
+            if queue.requests.is_empty() {
+                return;
+            }
+            mem::replace(&mut *queue, Queue::new(self.linger)).requests
+        };
+
+        trace!("Flushing batch of {} calls.", requests.len());
+        let sent = self.transport.send_batch(requests).shared();
+        let mut slot = slot.lock();
+        slot.sent = Some(sent);
+        for task in slot.waiting.drain(..) {
+            task.notify();
+        }
+    }
+}
+
+/// Transport batching calls automatically.
+///
+/// A batch is sent as soon as it reaches the maximal size
+/// or when the linger time since the first call in it has passed.
+pub struct AutoBatch<T: BatchTransport> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T: BatchTransport> Clone for AutoBatch<T> {
+    fn clone(&self) -> Self {
+        AutoBatch {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: BatchTransport + fmt::Debug> fmt::Debug for AutoBatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AutoBatch")
+            .field("transport", &self.inner.transport)
+            .field("max_size", &self.inner.max_size)
+            .field("linger", &self.inner.linger)
+            .field("queued", &self.queued())
+            .finish()
+    }
+}
+
+impl<T: BatchTransport> AutoBatch<T> {
+    /// Creates new auto-batching transport with default limits.
+    pub fn new(transport: T) -> Self {
+        Self::with_limits(transport, DEFAULT_MAX_SIZE, Duration::from_millis(DEFAULT_LINGER_MS))
+    }
+
+    /// Creates new auto-batching transport sending batches of at most `max_size` calls
+    /// or after `linger` time since the first call in the batch.
+    ///
+    /// Starts a dedicated timer thread, use `with_timer` to share one between transports.
+    /// The timer ticks every millisecond for lingers up to about 4 seconds, longer lingers use longer ticks.
+    pub fn with_limits(transport: T, max_size: usize, linger: Duration) -> Self {
+        let linger_ms = linger
+            .as_secs()
+            .saturating_mul(1_000)
+            .saturating_add(u64::from(linger.subsec_nanos() / 1_000_000));
+        let tick_ms = linger_ms / TIMER_SLOTS as u64 + 1;
+        let timer = tokio_timer::wheel()
+            .tick_duration(Duration::from_millis(tick_ms))
+            .num_slots(TIMER_SLOTS)
+            .max_timeout(Duration::from_millis(tick_ms.saturating_mul(TIMER_SLOTS as u64)))
+            .build();
+        Self::with_timer(transport, max_size, linger, timer)
+    }
+
+    /// Creates new auto-batching transport using given timer to track the linger time.
+    ///
+    /// The linger time is rounded up to the timer's tick duration and has to be within
+    /// the timer's maximal timeout (`tick_duration * num_slots` by default, about 409s for `Timer::default()`).
+    /// If it's longer, the timer fails and batches are sent as soon as they are polled.
+    pub fn with_timer(transport: T, max_size: usize, linger: Duration, timer: Timer) -> Self {
+        AutoBatch {
+            inner: Arc::new(Inner {
+                transport,
+                max_size: ::std::cmp::max(max_size, 1),
+                linger,
+                queue: Mutex::new(Queue::new(linger)),
+                timer,
+            }),
+        }
+    }
+
+    /// Sends all queued calls immediately.
+    pub fn flush(&self) {
+        let slot = self.inner.queue.lock().slot.clone();
+        self.inner.flush(&slot);
+    }
+
+    /// Returns number of calls waiting for the batch to be sent.
+    pub fn queued(&self) -> usize {
+        self.inner.queue.lock().requests.len()
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.inner.transport
+    }
+}
+
+impl<T: BatchTransport> Transport for AutoBatch<T> {
+    type Out = BatchedCall<T>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.inner.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let (index, slot, deadline, full) = {
+            let mut queue = self.inner.queue.lock();
+            if queue.requests.is_empty() {
+                queue.deadline = Instant::now() + self.inner.linger;
+            }
+            queue.requests.push((id, request));
+            (
+                queue.requests.len() - 1,
+                queue.slot.clone(),
+                queue.deadline,
+                queue.requests.len() >= self.inner.max_size,
+            )
+        };
+
+        if full {
+            self.inner.flush(&slot);
+        }
+
+        BatchedCall {
+            inner: self.inner.clone(),
+            slot,
+            index,
+            deadline,
+            task: None,
+            linger: None,
+            sent: None,
+        }
+    }
+}
+
+/// A future resolving to the result of a single call within an automatically sent batch.
+pub struct BatchedCall<T: BatchTransport> {
+    inner: Arc<Inner<T>>,
+    slot: SharedSlot<T::Batch>,
+    index: usize,
+    deadline: Instant,
+    task: Option<Arc<AtomicTask>>,
+    linger: Option<Sleep>,
+    sent: Option<Shared<T::Batch>>,
+}
+
+impl<T: BatchTransport> Future for BatchedCall<T> {
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            if let Some(ref mut sent) = self.sent {
+                let results = match sent.poll() {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(results)) => results,
+                    Err(err) => return Err((*err).clone()),
+                };
+                return match results.get(self.index) {
+                    Some(result) => result.clone().map(Async::Ready),
+                    None => Err(ErrorKind::Internal.into()),
+                };
+            }
+
+            {
+                let mut slot = self.slot.lock();
+                if let Some(ref sent) = slot.sent {
+                    self.sent = Some(sent.clone());
+                    continue;
+                }
+                match self.task {
+                    Some(ref task) => task.register(),
+                    None => {
+                        let task = Arc::new(AtomicTask::new());
+                        task.register();
+                        slot.waiting.push(task.clone());
+                        self.task = Some(task);
+                    }
+                }
+            }
+
+            if self.linger.is_none() {
+                let now = Instant::now();
+                let wait = if self.deadline > now {
+                    self.deadline - now
+                } else {
+                    Duration::from_millis(0)
+                };
+                self.linger = Some(self.inner.timer.sleep(wait));
+            }
+
+            match self.linger.as_mut().map(Future::poll) {
+                Some(Ok(Async::NotReady)) => return Ok(Async::NotReady),
+                Some(Ok(Async::Ready(()))) | None => {}
+                Some(Err(err)) => {
+                    warn!("Batch linger timer failed: {:?}. Sending batch now.", err);
+                }
+            }
+            self.inner.flush(&self.slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use futures::{self, Future};
+    use parking_lot::Mutex;
+    use tokio_timer;
+    use rpc;
+    use api::{Cita, Namespace};
+    use transports::Result;
+    use types::U256;
+    use {helpers, BatchTransport, ErrorKind, RequestId, Transport};
+    use super::AutoBatch;
+
+    /// Responds to every call with its position in the batch.
+    #[derive(Debug, Default, Clone)]
+    struct Counting {
+        batches: Arc<Mutex<Vec<usize>>>,
+        fail: bool,
+    }
+
+    impl Transport for Counting {
+        type Out = ::Result<rpc::Value>;
+
+        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+            (1, helpers::build_request(1, method, params))
+        }
+
+        fn send(&self, _id: RequestId, _request: rpc::Call) -> Self::Out {
+            unreachable!("Calls are always batched.")
+        }
+    }
+
+    impl BatchTransport for Counting {
+        type Batch = ::Result<Vec<Result<rpc::Value>>>;
+
+        fn send_batch<T>(&self, requests: T) -> Self::Batch
+        where
+            T: IntoIterator<Item = (RequestId, rpc::Call)>,
+        {
+            let size = requests.into_iter().count();
+            self.batches.lock().push(size);
+            if self.fail {
+                return Box::new(futures::failed(ErrorKind::Unreachable.into()));
+            }
+            Box::new(futures::finished(
+                (0..size)
+                    .map(|idx| Ok(helpers::serialize(&U256::from(idx as u64))))
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    fn should_flush_full_batch() {
+        // given
+        let transport = Counting::default();
+        let batch = AutoBatch::with_limits(&transport, 2, Duration::from_secs(60));
+        let cita = Cita::new(&batch);
+
+        // when
+        let first = cita.block_number();
+        let second = cita.block_number();
+        let third = cita.block_number();
+
+        // then
+        assert_eq!(first.wait(), Ok(U256::from(0)));
+        assert_eq!(second.wait(), Ok(U256::from(1)));
+        assert_eq!(*transport.batches.lock(), vec![2]);
+        assert_eq!(batch.queued(), 1);
+        batch.flush();
+        assert_eq!(third.wait(), Ok(U256::from(0)));
+        assert_eq!(*transport.batches.lock(), vec![2, 1]);
+    }
+
+    #[test]
+    fn should_flush_after_linger_time() {
+        // given
+        let transport = Counting::default();
+        let batch = AutoBatch::with_limits(&transport, 10, Duration::from_millis(20));
+        let cita = Cita::new(&batch);
+
+        // when
+        let first = cita.block_number();
+        let second = cita.block_number();
+
+        // then
+        assert_eq!(second.wait(), Ok(U256::from(1)));
+        assert_eq!(first.wait(), Ok(U256::from(0)));
+        assert_eq!(*transport.batches.lock(), vec![2]);
+    }
+
+    #[test]
+    fn should_deliver_batch_error_to_every_call() {
+        // given
+        let transport = Counting {
+            fail: true,
+            ..Default::default()
+        };
+        let batch = AutoBatch::with_limits(&transport, 2, Duration::from_millis(20));
+        let cita = Cita::new(&batch);
+
+        // when
+        let first = cita.block_number();
+        let second = cita.block_number();
+
+        // then
+        assert_eq!(first.wait(), Err(ErrorKind::Unreachable.into()));
+        assert_eq!(second.wait(), Err(ErrorKind::Unreachable.into()));
+    }
+
+    #[test]
+    fn should_not_send_empty_batch() {
+        // given
+        let transport = Counting::default();
+        let batch = AutoBatch::new(transport.clone());
+
+        // when
+        batch.flush();
+
+        // then
+        assert_eq!(*transport.batches.lock(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn should_share_timer_between_transports() {
+        // given
+        let timer = tokio_timer::wheel().tick_duration(Duration::from_millis(1)).build();
+        let transport = Counting::default();
+        let first = AutoBatch::with_timer(transport.clone(), 10, Duration::from_millis(5), timer.clone());
+        let second = AutoBatch::with_timer(transport.clone(), 10, Duration::from_millis(5), timer);
+
+        // when
+        let a = Cita::new(&first).block_number();
+        let b = Cita::new(&second).block_number();
+
+        // then
+        assert_eq!(a.wait(), Ok(U256::from(0)));
+        assert_eq!(b.wait(), Ok(U256::from(0)));
+        assert_eq!(*transport.batches.lock(), vec![1, 1]);
+    }
+
+    #[test]
+    fn should_linger_longer_than_default_timer_wheel() {
+        // given
+        let transport = Counting::default();
+        let batch = AutoBatch::with_limits(&transport, 10, Duration::from_secs(10));
+        let mut call = Cita::new(&batch).block_number();
+
+        // when
+        let polled = futures::lazy(|| Ok::<_, ()>(call.poll())).wait().unwrap();
+
+        // then
+        assert_eq!(polled, Ok(futures::Async::NotReady));
+        assert_eq!(batch.queued(), 1);
+        batch.flush();
+        assert_eq!(call.wait(), Ok(U256::from(0)));
+        assert_eq!(*transport.batches.lock(), vec![1]);
+    }
+}
@@ -47,6 +47,27 @@ where
             pending,
         }
     }
+
+    /// Returns `true` if the result belongs to a request queued in this batch.
+    pub(crate) fn owns(&self, result: &SingleResult) -> bool {
+        Arc::ptr_eq(&self.pending, &result.batch)
+    }
+
+    /// Sends requests with given ids as a batch, other queued requests fail without being sent.
+    pub(crate) fn submit_only(&self, ids: &[RequestId]) -> BatchFuture<T::Batch> {
+        {
+            let mut batch = self.batch.lock();
+            let mut pending = self.pending.lock();
+            for (id, request) in mem::replace(&mut *batch, vec![]) {
+                if ids.contains(&id) {
+                    batch.push((id, request));
+                } else if let Some(tx) = pending.remove(&id) {
+                    let _ = tx.send(Err(ErrorKind::Transport("Call was not added to the batch.".into()).into()));
+                }
+            }
+        }
+        self.submit_batch()
+    }
 }
 
 impl<T> Transport for Batch<T>
@@ -64,7 +85,11 @@ where
         self.pending.lock().insert(id, tx);
         self.batch.lock().push((id, request));
 
-        SingleResult(rx)
+        SingleResult {
+            id,
+            rx,
+            batch: self.pending.clone(),
+        }
     }
 }
 
@@ -133,7 +158,18 @@ impl<T: Future<Item = Vec<Result<rpc::Value>>, Error = RpcError>> Future for Bat
 
 /// Result of calling a single method that will be part of the batch.
 /// Converts `oneshot::Receiver` error into `RpcError::Internal`
-pub struct SingleResult(oneshot::Receiver<Result<rpc::Value>>);
+pub struct SingleResult {
+    id: RequestId,
+    rx: oneshot::Receiver<Result<rpc::Value>>,
+    batch: PendingRequests,
+}
+
+impl SingleResult {
+    /// Returns id of the request.
+    pub(crate) fn id(&self) -> RequestId {
+        self.id
+    }
+}
 
 impl Future for SingleResult {
     type Item = rpc::Value;
@@ -141,7 +177,7 @@ impl Future for SingleResult {
 
     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
         let res = try_ready!(
-            self.0
+            self.rx
                 .poll()
                 .map_err(|_| RpcError::from(ErrorKind::Internal))
         );
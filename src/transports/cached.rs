@@ -0,0 +1,550 @@
+//! Caching Transport
+//!
+//! Caches responses to queries which never change once answered,
+//! like blocks by hash or state at a fixed block number.
+//! Queries depending on `latest` or `pending` state are never cached.
+//! CITA blocks are final as soon as they are committed (BFT consensus), so there are no reorganizations.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Poll};
+use parking_lot::Mutex;
+use rpc;
+use transports::Result;
+use {helpers, BatchTransport, Error, RequestId, Transport};
+
+/// Decides if a response to given method and params may be cached.
+pub type CachePolicy = Arc<Fn(&str, &[rpc::Value]) -> bool + Send + Sync>;
+
+/// Returns `true` if the parameter is a fixed block: a block number (not `latest`, `pending` or `earliest`)
+/// or a block hash (EIP-1898 `{"blockHash": ...}`).
+fn is_fixed_block(param: Option<&rpc::Value>) -> bool {
+    match param {
+        Some(&rpc::Value::String(ref block)) => block.starts_with("0x"),
+        Some(&rpc::Value::Object(ref obj)) => obj.get("blockHash").map_or(false, rpc::Value::is_string),
+        _ => false,
+    }
+}
+
+/// Default cache policy.
+///
+/// Caches lookups by hash and state queries at a fixed block.
+pub fn is_immutable(method: &str, params: &[rpc::Value]) -> bool {
+    match method {
+        "cita_getBlockByHash" | "eth_getBlockByHash" => true,
+        "cita_getTransaction" | "eth_getTransactionByHash" | "eth_getTransactionReceipt" => true,
+        "cita_getBlockByNumber" | "eth_getBlockByNumber" => is_fixed_block(params.get(0)),
+        "eth_getCode" | "eth_getBalance" | "eth_getTransactionCount" | "eth_call" => is_fixed_block(params.get(1)),
+        "eth_getStorageAt" => is_fixed_block(params.get(2)),
+        _ => false,
+    }
+}
+
+/// Returns `true` if the response is final and can be stored.
+/// Missing (`null`) objects, transactions and receipts not yet included in a block may still change.
+fn is_final(method: &str, response: &rpc::Value) -> bool {
+    match *response {
+        rpc::Value::Null => false,
+        rpc::Value::Object(ref obj) => match method {
+            "cita_getTransaction" | "eth_getTransactionByHash" | "eth_getTransactionReceipt" => {
+                obj.get("blockHash").map_or(false, |hash| !hash.is_null())
+            }
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+/// Canonical cache key of the call (hex strings are lowercased).
+pub(crate) fn cache_key(method: &str, params: &[rpc::Value]) -> String {
+    fn canonical(value: &rpc::Value) -> rpc::Value {
+        match *value {
+            rpc::Value::String(ref s) if s.starts_with("0x") => rpc::Value::String(s.to_lowercase()),
+            rpc::Value::Array(ref values) => rpc::Value::Array(values.iter().map(canonical).collect()),
+            rpc::Value::Object(ref obj) => rpc::Value::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), canonical(v)))
+                    .collect(),
+            ),
+            ref other => other.clone(),
+        }
+    }
+
+    let params = params.iter().map(canonical).collect::<Vec<_>>();
+    format!("{}:{}", method, helpers::to_string(&params))
+}
+
+/// Cache statistics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Requests served from the cache.
+    pub hits: u64,
+    /// Cacheable requests sent to the node.
+    pub misses: u64,
+    /// Entries removed because of size limit.
+    pub evictions: u64,
+    /// Entries removed because they were older than TTL.
+    pub expirations: u64,
+    /// Number of entries currently stored.
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: rpc::Value,
+    inserted: Instant,
+    used: u64,
+}
+
+/// Least recently used cache.
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    ttl: Option<Duration>,
+    entries: HashMap<String, Entry>,
+    order: BTreeMap<u64, String>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl Lru {
+    fn get(&mut self, key: &str) -> Option<rpc::Value> {
+        let expired = match self.entries.get(key) {
+            Some(entry) => self.ttl.map_or(false, |ttl| entry.inserted.elapsed() > ttl),
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+
+        if expired {
+            self.remove(key);
+            self.stats.expirations += 1;
+            self.stats.misses += 1;
+            return None;
+        }
+
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self.entries
+            .get_mut(key)
+            .expect("Entry presence checked above; qed");
+        self.order.remove(&entry.used);
+        self.order.insert(clock, key.to_owned());
+        entry.used = clock;
+        self.stats.hits += 1;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: String, value: rpc::Value) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.remove(&key);
+        while self.entries.len() >= self.capacity {
+            let oldest = match self.order.iter().next() {
+                Some((_, key)) => key.clone(),
+                None => break,
+            };
+            self.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+
+        self.clock += 1;
+        self.order.insert(self.clock, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                inserted: Instant::now(),
+                used: self.clock,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.used);
+        }
+    }
+}
+
+/// Transport caching responses to immutable queries.
+#[derive(Clone)]
+pub struct Cached<T> {
+    transport: T,
+    policy: CachePolicy,
+    cache: Arc<Mutex<Lru>>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Cached<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cached")
+            .field("transport", &self.transport)
+            .field("stats", &self.cache.lock().stats)
+            .finish()
+    }
+}
+
+impl<T: Transport> Cached<T> {
+    /// Creates new caching transport storing at most `capacity` responses.
+    pub fn new(transport: T, capacity: usize) -> Self {
+        Cached {
+            transport,
+            policy: Arc::new(is_immutable),
+            cache: Arc::new(Mutex::new(Lru {
+                capacity,
+                ttl: None,
+                entries: Default::default(),
+                order: Default::default(),
+                clock: 0,
+                stats: Default::default(),
+            })),
+        }
+    }
+
+    /// Expires entries older than `ttl`.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.cache.lock().ttl = Some(ttl);
+        self
+    }
+
+    /// Uses custom policy deciding which calls may be cached.
+    pub fn with_policy<F>(mut self, policy: F) -> Self
+    where
+        F: Fn(&str, &[rpc::Value]) -> bool + Send + Sync + 'static,
+    {
+        self.policy = Arc::new(policy);
+        self
+    }
+
+    /// Returns cache statistics.
+    pub fn stats(&self) -> CacheStats {
+        let cache = self.cache.lock();
+        CacheStats {
+            entries: cache.entries.len(),
+            ..cache.stats
+        }
+    }
+
+    /// Removes all cached entries.
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock();
+        cache.entries.clear();
+        cache.order.clear();
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns cache key of the call if it can be cached.
+    fn key(&self, request: &rpc::Call) -> Option<(String, String)> {
+        match *request {
+            rpc::Call::MethodCall(rpc::MethodCall {
+                ref method,
+                params: Some(rpc::Params::Array(ref params)),
+                ..
+            }) if (self.policy)(method, params) =>
+            {
+                Some((method.clone(), cache_key(method, params)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<T: Transport> Transport for Cached<T> {
+    type Out = CachedCall<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let key = self.key(&request);
+        if let Some((_, ref key)) = key {
+            if let Some(value) = self.cache.lock().get(key) {
+                trace!("[{}] Served from cache: {}", id, key);
+                return CachedCall {
+                    inner: None,
+                    hit: Some(value),
+                    key: None,
+                    cache: self.cache.clone(),
+                };
+            }
+        }
+
+        CachedCall {
+            inner: Some(self.transport.send(id, request)),
+            hit: None,
+            key,
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+/// A future resolving to a cached response or response of the inner transport.
+pub struct CachedCall<F> {
+    inner: Option<F>,
+    hit: Option<rpc::Value>,
+    key: Option<(String, String)>,
+    cache: Arc<Mutex<Lru>>,
+}
+
+impl<F> Future for CachedCall<F>
+where
+    F: Future<Item = rpc::Value, Error = Error>,
+{
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let value = match self.inner {
+            Some(ref mut inner) => try_ready!(inner.poll()),
+            None => return Ok(Async::Ready(self.hit.take().expect("future polled after ready; qed"))),
+        };
+
+        if let Some((method, key)) = self.key.take() {
+            if is_final(&method, &value) {
+                self.cache.lock().insert(key, value.clone());
+            }
+        }
+        Ok(Async::Ready(value))
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Cached<T> {
+    type Batch = CachedBatch<T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let mut results = vec![];
+        let mut misses = vec![];
+        let mut keys = vec![];
+        {
+            let mut cache = self.cache.lock();
+            for (idx, (id, request)) in requests.into_iter().enumerate() {
+                let key = self.key(&request);
+                let hit = key.as_ref().and_then(|&(_, ref key)| cache.get(key));
+                match hit {
+                    Some(value) => results.push(Some(Ok(value))),
+                    None => {
+                        results.push(None);
+                        keys.push((idx, key));
+                        misses.push((id, request));
+                    }
+                }
+            }
+        }
+
+        let inner = if misses.is_empty() {
+            None
+        } else {
+            Some(self.transport.send_batch(misses))
+        };
+
+        CachedBatch {
+            inner,
+            results,
+            keys,
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+/// A batch future serving cached responses and sending only the remaining calls.
+pub struct CachedBatch<F> {
+    inner: Option<F>,
+    results: Vec<Option<Result<rpc::Value>>>,
+    keys: Vec<(usize, Option<(String, String)>)>,
+    cache: Arc<Mutex<Lru>>,
+}
+
+impl<F> Future for CachedBatch<F>
+where
+    F: Future<Item = Vec<Result<rpc::Value>>, Error = Error>,
+{
+    type Item = Vec<Result<rpc::Value>>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(ref mut inner) = self.inner {
+            let responses = try_ready!(inner.poll());
+            let mut cache = self.cache.lock();
+            let mut responses = responses.into_iter();
+            for (idx, key) in self.keys.drain(..) {
+                let response = responses.next().unwrap_or_else(|| Err(::ErrorKind::Internal.into()));
+                if let (Some((method, key)), &Ok(ref value)) = (key, &response) {
+                    if is_final(&method, value) {
+                        cache.insert(key, value.clone());
+                    }
+                }
+                self.results[idx] = Some(response);
+            }
+        }
+
+        let results = self.results
+            .drain(..)
+            .map(|result| result.expect("All results are filled in; qed"))
+            .collect();
+        Ok(Async::Ready(results))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use futures::Future;
+    use rpc::Value;
+    use api::{Cita, Namespace};
+    use helpers::tests::TestTransport;
+    use types::BlockNumber;
+    use Transport;
+    use super::{cache_key, is_immutable, Cached};
+
+    fn block_number() -> Value {
+        Value::String("0x1".into())
+    }
+
+    #[test]
+    fn should_only_cache_immutable_queries() {
+        let hash = Value::String("0x01".into());
+        let by_hash = json!({"blockHash": "0x01"});
+        let fixed = Value::String("0x10".into());
+        let latest = Value::String("latest".into());
+        let address = Value::String("0x0000000000000000000000000000000000000123".into());
+
+        assert!(is_immutable("cita_getBlockByHash", &[hash.clone(), Value::Bool(false)]));
+        assert!(is_immutable("cita_getBlockByNumber", &[fixed.clone(), Value::Bool(false)]));
+        assert!(is_immutable("eth_getCode", &[address.clone(), fixed]));
+        assert!(is_immutable("eth_getCode", &[address.clone(), by_hash]));
+        assert!(is_immutable("eth_getTransactionReceipt", &[hash.clone()]));
+        assert!(is_immutable("cita_getTransaction", &[hash.clone()]));
+        assert!(!is_immutable("cita_getBlockByNumber", &[latest.clone(), Value::Bool(false)]));
+        assert!(!is_immutable("eth_getCode", &[address, latest]));
+        assert!(!is_immutable("eth_getCode", &[hash, Value::String("pending".into())]));
+        assert!(!is_immutable("cita_blockNumber", &[]));
+    }
+
+    #[test]
+    fn should_canonicalize_keys() {
+        assert_eq!(
+            cache_key("eth_getBlockByHash", &[Value::String("0xABcd".into())]),
+            cache_key("eth_getBlockByHash", &[Value::String("0xabcd".into())])
+        );
+    }
+
+    #[test]
+    fn should_serve_repeated_queries_from_cache() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(block_number());
+        transport.add_response(block_number());
+        transport.add_response(json!({"hash": "0x01"}));
+        let (block1, block2, stats) = {
+            let cached = Cached::new(&transport, 10);
+            let cita = Cita::new(&cached);
+            let params = vec![Value::String("0x01".into()), Value::Bool(false)];
+
+            // when
+            cita.block_number().wait().unwrap();
+            cita.block_number().wait().unwrap();
+            let block1 = cached.execute("cita_getBlockByHash", params.clone()).wait();
+            let block2 = cached.execute("cita_getBlockByHash", params).wait();
+            (block1, block2, cached.stats())
+        };
+
+        // then
+        // The response queue is empty, so the second lookup could only come from the cache.
+        assert_eq!(block1, Ok(json!({"hash": "0x01"})));
+        assert_eq!(block1, block2);
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+        // Requests are recorded when prepared, including the one served from cache.
+        transport.assert_request("cita_blockNumber", &[]);
+        transport.assert_request("cita_blockNumber", &[]);
+        transport.assert_request("cita_getBlockByHash", &[r#""0x01""#.into(), "false".into()]);
+        transport.assert_request("cita_getBlockByHash", &[r#""0x01""#.into(), "false".into()]);
+        transport.assert_no_more_requests();
+    }
+
+    #[test]
+    fn should_not_cache_missing_objects() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::Null);
+        transport.add_response(Value::Null);
+        let cached = Cached::new(&transport, 10);
+
+        // when
+        let params = vec![Value::String("0x01".into()), Value::Bool(false)];
+        cached.execute("cita_getBlockByHash", params.clone()).wait().unwrap();
+        cached.execute("cita_getBlockByHash", params).wait().unwrap();
+
+        // then
+        assert_eq!(cached.stats().misses, 2);
+        assert_eq!(cached.stats().entries, 0);
+    }
+
+    #[test]
+    fn should_cache_transactions_and_receipts_once_included() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(json!({"hash": "0x01", "blockHash": null}));
+        transport.add_response(json!({"hash": "0x01", "blockHash": "0x02"}));
+        transport.add_response(json!({"transactionHash": "0x01", "blockHash": null}));
+        transport.add_response(json!({"transactionHash": "0x01", "blockHash": "0x02"}));
+        let cached = Cached::new(&transport, 10);
+        let params = vec![Value::String("0x01".into())];
+        let call = |method| cached.execute(method, params.clone()).wait().unwrap();
+
+        // when
+        let pending = call("cita_getTransaction");
+        let included = call("cita_getTransaction");
+        let pending_receipt = call("eth_getTransactionReceipt");
+        let receipt = call("eth_getTransactionReceipt");
+
+        // then
+        assert_eq!(pending["blockHash"], Value::Null);
+        assert_eq!(call("cita_getTransaction"), included);
+        assert_eq!(pending_receipt["blockHash"], Value::Null);
+        assert_eq!(call("eth_getTransactionReceipt"), receipt);
+        assert_eq!((cached.stats().hits, cached.stats().entries), (2, 2));
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_and_expired_entries() {
+        // given
+        let mut transport = TestTransport::default();
+        for i in 0..4 {
+            transport.add_response(Value::String(format!("0x{}", i)));
+        }
+        let cached = Cached::new(&transport, 2).with_ttl(Duration::from_millis(50));
+        let code = |block: u64| {
+            Cita::new(&cached)
+                .code(0x123.into(), Some(BlockNumber::Number(block)))
+                .wait()
+                .unwrap()
+        };
+
+        // when
+        code(1);
+        code(2);
+        code(1);
+        code(3);
+        let stats = cached.stats();
+        thread::sleep(Duration::from_millis(60));
+        code(1);
+
+        // then
+        assert_eq!((stats.hits, stats.evictions, stats.entries), (1, 1, 2));
+        assert_eq!(cached.stats().expirations, 1);
+    }
+}
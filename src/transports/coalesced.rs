@@ -0,0 +1,344 @@
+//! Coalescing Transport
+//!
+//! Sends identical requests issued while the first one is still in flight only once
+//! and resolves all of them with the same response.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use futures::{Async, Future, Poll};
+use futures::future::Shared;
+use parking_lot::Mutex;
+use rpc;
+use transports::cached::cache_key;
+use {Error, RequestId, Transport};
+
+/// Decides if a call may share the response of an identical in-flight call.
+pub type CoalescePolicy = Arc<Fn(&str, &[rpc::Value]) -> bool + Send + Sync>;
+
+/// Default coalescing policy.
+///
+/// Excludes calls with side effects, which must reach the node every time they are made:
+/// filter installation, polling (it drains the changes) and removal, sending transactions,
+/// managing accounts and submitting mining work.
+pub fn is_coalescable(method: &str, _params: &[rpc::Value]) -> bool {
+    let name = method.splitn(2, '_').nth(1).unwrap_or(method);
+    match name {
+        "newFilter" | "newBlockFilter" | "newPendingTransactionFilter" | "uninstallFilter" => false,
+        "getFilterChanges" => false,
+        "sendTransaction" | "sendRawTransaction" => false,
+        _ => match method {
+            "personal_newAccount" | "personal_unlockAccount" => false,
+            "eth_submitWork" | "eth_submitHashrate" => false,
+            _ => true,
+        },
+    }
+}
+
+struct InFlight<F: Future> {
+    future: Shared<F>,
+    generation: u64,
+    waiters: usize,
+}
+
+struct Inner<F: Future> {
+    generation: u64,
+    requests: HashMap<String, InFlight<F>>,
+}
+
+impl<F: Future> Inner<F> {
+    /// Removes the request if it's still the same one that was registered.
+    fn release(&mut self, key: &str, generation: u64, completed: bool) {
+        let remove = match self.requests.get_mut(key) {
+            Some(ref mut request) => {
+                if request.generation != generation {
+                    return;
+                }
+                request.waiters -= 1;
+                completed || request.waiters == 0
+            }
+            None => false,
+        };
+
+        if remove {
+            self.requests.remove(key);
+        }
+    }
+}
+
+/// Transport sending identical in-flight requests only once.
+///
+/// Requests are identical if they call the same method with the same parameters.
+pub struct Coalesced<T: Transport> {
+    transport: T,
+    policy: CoalescePolicy,
+    inner: Arc<Mutex<Inner<T::Out>>>,
+}
+
+impl<T: Transport + Clone> Clone for Coalesced<T> {
+    fn clone(&self) -> Self {
+        Coalesced {
+            transport: self.transport.clone(),
+            policy: self.policy.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Transport + fmt::Debug> fmt::Debug for Coalesced<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Coalesced")
+            .field("transport", &self.transport)
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+impl<T: Transport> Coalesced<T> {
+    /// Creates new coalescing transport.
+    pub fn new(transport: T) -> Self {
+        Coalesced {
+            transport,
+            policy: Arc::new(is_coalescable),
+            inner: Arc::new(Mutex::new(Inner {
+                generation: 0,
+                requests: Default::default(),
+            })),
+        }
+    }
+
+    /// Uses custom policy deciding which calls may be coalesced.
+    pub fn with_policy<F>(mut self, policy: F) -> Self
+    where
+        F: Fn(&str, &[rpc::Value]) -> bool + Send + Sync + 'static,
+    {
+        self.policy = Arc::new(policy);
+        self
+    }
+
+    /// Returns number of distinct requests currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.inner.lock().requests.len()
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Transport for Coalesced<T> {
+    type Out = CoalescedCall<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let key = match request {
+            rpc::Call::MethodCall(rpc::MethodCall {
+                ref method,
+                params: Some(rpc::Params::Array(ref params)),
+                ..
+            }) if (self.policy)(method, params) =>
+            {
+                cache_key(method, params)
+            }
+            _ => {
+                return CoalescedCall {
+                    future: self.transport.send(id, request).shared(),
+                    key: None,
+                    inner: self.inner.clone(),
+                }
+            }
+        };
+
+        let mut inner = self.inner.lock();
+        if let Some(request) = inner.requests.get_mut(&key) {
+            trace!("[{}] Joining in-flight request: {}", id, key);
+            request.waiters += 1;
+            return CoalescedCall {
+                future: request.future.clone(),
+                key: Some((key, request.generation)),
+                inner: self.inner.clone(),
+            };
+        }
+
+        inner.generation += 1;
+        let generation = inner.generation;
+        let future = self.transport.send(id, request).shared();
+        inner.requests.insert(
+            key.clone(),
+            InFlight {
+                future: future.clone(),
+                generation,
+                waiters: 1,
+            },
+        );
+
+        CoalescedCall {
+            future,
+            key: Some((key, generation)),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// A future resolving to the response of a (possibly shared) request.
+pub struct CoalescedCall<F: Future> {
+    future: Shared<F>,
+    key: Option<(String, u64)>,
+    inner: Arc<Mutex<Inner<F>>>,
+}
+
+impl<F: Future> CoalescedCall<F> {
+    fn release(&mut self, completed: bool) {
+        if let Some((key, generation)) = self.key.take() {
+            self.inner.lock().release(&key, generation, completed);
+        }
+    }
+}
+
+impl<F: Future> Drop for CoalescedCall<F> {
+    fn drop(&mut self) {
+        self.release(false);
+    }
+}
+
+impl<F> Future for CoalescedCall<F>
+where
+    F: Future<Item = rpc::Value, Error = Error>,
+{
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let result = match self.future.poll() {
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(value)) => Ok(Async::Ready((*value).clone())),
+            Err(err) => Err((*err).clone()),
+        };
+
+        self.release(true);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Future;
+    use rpc::Value;
+    use api::{Cita, Namespace};
+    use helpers::tests::TestTransport;
+    use types::U256;
+    use {ErrorKind, Transport};
+    use super::{is_coalescable, Coalesced};
+
+    #[test]
+    fn should_send_identical_requests_once() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        let coalesced = Coalesced::new(&transport);
+        let cita = Cita::new(&coalesced);
+
+        // when
+        let first = cita.block_number();
+        let second = cita.block_number();
+        assert_eq!(coalesced.in_flight(), 1);
+
+        // then
+        assert_eq!(first.wait(), Ok(U256::from(0x10)));
+        assert_eq!(second.wait(), Ok(U256::from(0x10)));
+        assert_eq!(coalesced.in_flight(), 0);
+    }
+
+    #[test]
+    fn should_fan_out_errors() {
+        // given
+        let transport = TestTransport::default();
+        let coalesced = Coalesced::new(&transport);
+        let cita = Cita::new(&coalesced);
+
+        // when
+        let first = cita.block_number();
+        let second = cita.block_number();
+
+        // then
+        assert_eq!(first.wait(), Err(ErrorKind::Unreachable.into()));
+        assert_eq!(second.wait(), Err(ErrorKind::Unreachable.into()));
+        assert_eq!(coalesced.in_flight(), 0);
+    }
+
+    #[test]
+    fn should_forget_requests_dropped_by_all_waiters() {
+        // given
+        let transport = TestTransport::default();
+        let coalesced = Coalesced::new(&transport);
+        let cita = Cita::new(&coalesced);
+        let first = cita.block_number();
+        let second = cita.block_number();
+
+        // when
+        drop(first);
+        let in_flight = coalesced.in_flight();
+        drop(second);
+
+        // then
+        assert_eq!(in_flight, 1);
+        assert_eq!(coalesced.in_flight(), 0);
+    }
+
+    #[test]
+    fn should_not_coalesce_calls_with_side_effects() {
+        assert!(is_coalescable("cita_blockNumber", &[]));
+        assert!(is_coalescable("eth_getFilterLogs", &[]));
+        assert!(!is_coalescable("eth_getFilterChanges", &[]));
+        assert!(!is_coalescable("cita_getFilterChanges", &[]));
+        assert!(!is_coalescable("eth_newFilter", &[]));
+        assert!(!is_coalescable("cita_newBlockFilter", &[]));
+        assert!(!is_coalescable("eth_uninstallFilter", &[]));
+        assert!(!is_coalescable("eth_sendTransaction", &[]));
+        assert!(!is_coalescable("cita_sendRawTransaction", &[]));
+        assert!(!is_coalescable("personal_newAccount", &[]));
+        assert!(!is_coalescable("personal_unlockAccount", &[]));
+        assert!(!is_coalescable("eth_submitWork", &[]));
+        assert!(!is_coalescable("eth_submitHashrate", &[]));
+    }
+
+    #[test]
+    fn should_send_excluded_requests_separately() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let coalesced = Coalesced::new(&transport);
+
+        // when
+        let first = coalesced.execute("cita_getFilterChanges", vec![Value::String("0x1".into())]);
+        let second = coalesced.execute("cita_getFilterChanges", vec![Value::String("0x1".into())]);
+        assert_eq!(coalesced.in_flight(), 0);
+
+        // then
+        assert_eq!(first.wait(), Ok(Value::String("0x1".into())));
+        assert_eq!(second.wait(), Ok(Value::String("0x2".into())));
+    }
+
+    #[test]
+    fn should_use_custom_policy() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let coalesced = Coalesced::new(&transport).with_policy(|method, _| method != "cita_blockNumber");
+        let cita = Cita::new(&coalesced);
+
+        // when
+        let first = cita.block_number();
+        let second = cita.block_number();
+
+        // then
+        assert_eq!(first.wait(), Ok(U256::from(1)));
+        assert_eq!(second.wait(), Ok(U256::from(2)));
+    }
+}
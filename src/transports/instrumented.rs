@@ -0,0 +1,472 @@
+//! Instrumented Transport
+//!
+//! Wraps any transport and reports per-method counts, errors, latencies,
+//! in-flight requests and batch sizes to a `MetricsSink`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Poll};
+use parking_lot::Mutex;
+use rpc;
+use transports::Result;
+use {BatchTransport, Error, ErrorKind, RequestId, Transport};
+
+/// Upper bounds (in milliseconds) of latency histogram buckets.
+/// Requests slower than the last bound are counted in an extra bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 12] = [1, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// Outcome of a single call.
+#[derive(Debug, Clone, Copy)]
+pub enum Outcome<'a> {
+    /// The call succeeded.
+    Success,
+    /// The call failed with given error.
+    Failure(&'a Error),
+    /// The call future was dropped before completion.
+    Cancelled,
+}
+
+/// Receives metrics of an instrumented transport.
+pub trait MetricsSink: Send + Sync {
+    /// A call to `method` has been sent.
+    fn request_started(&self, id: RequestId, method: &str);
+
+    /// A call to `method` has finished after `elapsed` time.
+    fn request_finished(&self, id: RequestId, method: &str, elapsed: Duration, outcome: Outcome);
+
+    /// A batch of given size has been sent.
+    fn batch_sent(&self, size: usize);
+}
+
+/// Returns a short name of the error kind, used to group errors.
+pub fn error_kind_name(kind: &ErrorKind) -> &'static str {
+    match *kind {
+        ErrorKind::Io(_) => "io",
+        ErrorKind::Unreachable => "unreachable",
+        ErrorKind::Decoder(_) => "decoder",
+        ErrorKind::InvalidResponse(_) => "invalid_response",
+        ErrorKind::Transport(_) => "transport",
+        ErrorKind::Rpc(_) => "rpc",
+        ErrorKind::Internal => "internal",
+        ErrorKind::Timeout => "timeout",
+        _ => "other",
+    }
+}
+
+fn as_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000 + duration.subsec_nanos() as u64 / 1_000_000
+}
+
+/// Latency histogram.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    /// Number of samples in each of `LATENCY_BUCKETS_MS` buckets (plus overflow bucket).
+    pub buckets: Vec<u64>,
+    /// Number of samples.
+    pub count: u64,
+    /// Sum of all samples in milliseconds.
+    pub sum_ms: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let ms = as_millis(elapsed);
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        }
+        let idx = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or_else(|| LATENCY_BUCKETS_MS.len());
+        self.buckets[idx] += 1;
+        self.count += 1;
+        self.sum_ms += ms;
+    }
+}
+
+/// Statistics of a single method.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodMetrics {
+    /// Number of calls sent.
+    pub calls: u64,
+    /// Number of failed calls grouped by error kind.
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Number of calls dropped before completion.
+    pub cancelled: u64,
+    /// Number of calls currently in flight.
+    pub in_flight: u64,
+    /// Latency of finished calls.
+    pub latency: Histogram,
+}
+
+/// Snapshot of metrics collected by `MemoryMetrics`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Statistics per method.
+    pub methods: BTreeMap<String, MethodMetrics>,
+    /// Number of batches sent.
+    pub batches: u64,
+    /// Total number of calls sent within batches.
+    pub batched_calls: u64,
+}
+
+impl MetricsSnapshot {
+    /// Returns number of calls currently in flight (for all methods).
+    pub fn in_flight(&self) -> u64 {
+        self.methods.values().map(|m| m.in_flight).sum()
+    }
+}
+
+/// In-memory metrics sink.
+#[derive(Debug, Default)]
+pub struct MemoryMetrics {
+    metrics: Mutex<MetricsSnapshot>,
+}
+
+impl MemoryMetrics {
+    /// Returns a copy of metrics collected so far.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.metrics.lock().clone()
+    }
+
+    /// Clears all collected metrics except for in-flight gauges.
+    pub fn reset(&self) {
+        let mut metrics = self.metrics.lock();
+        let methods = metrics
+            .methods
+            .iter()
+            .filter(|&(_, m)| m.in_flight > 0)
+            .map(|(method, m)| {
+                (
+                    method.clone(),
+                    MethodMetrics {
+                        in_flight: m.in_flight,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        *metrics = MetricsSnapshot {
+            methods,
+            ..Default::default()
+        };
+    }
+}
+
+impl MetricsSink for MemoryMetrics {
+    fn request_started(&self, _id: RequestId, method: &str) {
+        let mut metrics = self.metrics.lock();
+        let entry = metrics
+            .methods
+            .entry(method.to_owned())
+            .or_insert_with(Default::default);
+        entry.calls += 1;
+        entry.in_flight += 1;
+    }
+
+    fn request_finished(&self, _id: RequestId, method: &str, elapsed: Duration, outcome: Outcome) {
+        let mut metrics = self.metrics.lock();
+        let entry = metrics
+            .methods
+            .entry(method.to_owned())
+            .or_insert_with(Default::default);
+        entry.in_flight = entry.in_flight.saturating_sub(1);
+        match outcome {
+            Outcome::Success => entry.latency.observe(elapsed),
+            Outcome::Failure(err) => {
+                entry.latency.observe(elapsed);
+                *entry.errors.entry(error_kind_name(err.kind())).or_insert(0) += 1;
+            }
+            Outcome::Cancelled => entry.cancelled += 1,
+        }
+    }
+
+    fn batch_sent(&self, size: usize) {
+        let mut metrics = self.metrics.lock();
+        metrics.batches += 1;
+        metrics.batched_calls += size as u64;
+    }
+}
+
+/// Transport reporting metrics of all calls to a `MetricsSink`.
+#[derive(Clone)]
+pub struct Instrumented<T> {
+    transport: T,
+    sink: Arc<MetricsSink>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Instrumented<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Instrumented")
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+impl<T: Transport> Instrumented<T> {
+    /// Creates new instrumented transport reporting to given sink.
+    pub fn new(transport: T, sink: Arc<MetricsSink>) -> Self {
+        Instrumented { transport, sink }
+    }
+
+    /// Creates new instrumented transport collecting metrics in memory.
+    /// Returns the collector so that snapshots can be taken.
+    pub fn in_memory(transport: T) -> (Self, Arc<MemoryMetrics>) {
+        let metrics = Arc::new(MemoryMetrics::default());
+        (Self::new(transport, metrics.clone()), metrics)
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+fn method_name(request: &rpc::Call) -> String {
+    match *request {
+        rpc::Call::MethodCall(ref call) => call.method.clone(),
+        rpc::Call::Notification(ref notification) => notification.method.clone(),
+        rpc::Call::Invalid(_) => "<invalid>".into(),
+    }
+}
+
+/// Tracks a single call from sending until completion.
+struct Tracker {
+    id: RequestId,
+    method: String,
+    started: Instant,
+    sink: Arc<MetricsSink>,
+    finished: bool,
+}
+
+impl Tracker {
+    fn start(id: RequestId, method: String, sink: Arc<MetricsSink>) -> Self {
+        debug!(target: "web3::rpc", "id={} method={} event=sent", id, method);
+        sink.request_started(id, &method);
+        Tracker {
+            id,
+            method,
+            started: Instant::now(),
+            sink,
+            finished: false,
+        }
+    }
+
+    fn finish(&mut self, outcome: Outcome) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+
+        let elapsed = self.started.elapsed();
+        match outcome {
+            Outcome::Success => debug!(
+                target: "web3::rpc",
+                "id={} method={} event=finished status=ok duration_ms={}",
+                self.id,
+                self.method,
+                as_millis(elapsed)
+            ),
+            Outcome::Failure(err) => debug!(
+                target: "web3::rpc",
+                "id={} method={} event=finished status=error kind={} duration_ms={} error={:?}",
+                self.id,
+                self.method,
+                error_kind_name(err.kind()),
+                as_millis(elapsed),
+                err.to_string()
+            ),
+            Outcome::Cancelled => debug!(
+                target: "web3::rpc",
+                "id={} method={} event=cancelled duration_ms={}",
+                self.id,
+                self.method,
+                as_millis(elapsed)
+            ),
+        }
+        self.sink
+            .request_finished(self.id, &self.method, elapsed, outcome);
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        self.finish(Outcome::Cancelled);
+    }
+}
+
+impl<T: Transport> Transport for Instrumented<T> {
+    type Out = InstrumentedCall<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let tracker = Tracker::start(id, method_name(&request), self.sink.clone());
+        InstrumentedCall {
+            inner: self.transport.send(id, request),
+            tracker,
+        }
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Instrumented<T> {
+    type Batch = InstrumentedBatch<T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let requests = requests.into_iter().collect::<Vec<_>>();
+        debug!(target: "web3::rpc", "event=batch size={}", requests.len());
+        self.sink.batch_sent(requests.len());
+
+        let trackers = requests
+            .iter()
+            .map(|&(id, ref request)| Tracker::start(id, method_name(request), self.sink.clone()))
+            .collect();
+
+        InstrumentedBatch {
+            inner: self.transport.send_batch(requests),
+            trackers,
+        }
+    }
+}
+
+/// A future reporting the outcome of a single call.
+pub struct InstrumentedCall<F> {
+    inner: F,
+    tracker: Tracker,
+}
+
+impl<F> Future for InstrumentedCall<F>
+where
+    F: Future<Item = rpc::Value, Error = Error>,
+{
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.inner.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(value)) => {
+                self.tracker.finish(Outcome::Success);
+                Ok(Async::Ready(value))
+            }
+            Err(err) => {
+                self.tracker.finish(Outcome::Failure(&err));
+                Err(err)
+            }
+        }
+    }
+}
+
+/// A future reporting outcomes of all calls within a batch.
+pub struct InstrumentedBatch<F> {
+    inner: F,
+    trackers: Vec<Tracker>,
+}
+
+impl<F> Future for InstrumentedBatch<F>
+where
+    F: Future<Item = Vec<Result<rpc::Value>>, Error = Error>,
+{
+    type Item = Vec<Result<rpc::Value>>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.inner.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(results)) => {
+                for (idx, tracker) in self.trackers.iter_mut().enumerate() {
+                    match results.get(idx) {
+                        Some(&Ok(_)) => tracker.finish(Outcome::Success),
+                        Some(&Err(ref err)) => tracker.finish(Outcome::Failure(err)),
+                        None => tracker.finish(Outcome::Failure(&ErrorKind::Internal.into())),
+                    }
+                }
+                Ok(Async::Ready(results))
+            }
+            Err(err) => {
+                for tracker in &mut self.trackers {
+                    tracker.finish(Outcome::Failure(&err));
+                }
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use futures::Future;
+    use rpc::Value;
+    use helpers::tests::TestTransport;
+    use {ErrorKind, Transport};
+    use super::{Histogram, Instrumented, MetricsSink, Outcome};
+
+    #[test]
+    fn should_collect_method_metrics() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        let (instrumented, metrics) = Instrumented::in_memory(&transport);
+
+        // when
+        let first = instrumented.execute("cita_blockNumber", vec![]);
+        assert_eq!(metrics.snapshot().in_flight(), 1);
+        first.wait().unwrap();
+        instrumented
+            .execute("cita_blockNumber", vec![])
+            .wait()
+            .unwrap_err();
+        drop(instrumented.execute("net_peerCount", vec![]));
+
+        // then
+        let snapshot = metrics.snapshot();
+        let block_number = &snapshot.methods["cita_blockNumber"];
+        assert_eq!(snapshot.in_flight(), 0);
+        assert_eq!(block_number.calls, 2);
+        assert_eq!(block_number.errors.get("unreachable"), Some(&1));
+        assert_eq!(block_number.latency.count, 2);
+        assert_eq!(snapshot.methods["net_peerCount"].cancelled, 1);
+    }
+
+    #[test]
+    fn should_bucket_latencies() {
+        let mut histogram = Histogram::default();
+
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_millis(400));
+        histogram.observe(Duration::from_secs(60));
+
+        assert_eq!(histogram.count, 3);
+        assert_eq!(histogram.sum_ms, 60_403);
+        assert_eq!(histogram.buckets[1], 1);
+        assert_eq!(histogram.buckets[7], 1);
+        assert_eq!(histogram.buckets[12], 1);
+    }
+
+    #[test]
+    fn should_only_report_once() {
+        struct Counter(::parking_lot::Mutex<usize>);
+        impl MetricsSink for Counter {
+            fn request_started(&self, _id: usize, _method: &str) {}
+            fn request_finished(&self, _id: usize, _method: &str, _elapsed: Duration, _outcome: Outcome) {
+                *self.0.lock() += 1;
+            }
+            fn batch_sent(&self, _size: usize) {}
+        }
+
+        let counter = ::std::sync::Arc::new(Counter(Default::default()));
+        let instrumented = Instrumented::new(TestTransport::default(), counter.clone());
+        let res = instrumented.execute("net_version", vec![]).wait();
+
+        assert_eq!(res, Err(ErrorKind::Unreachable.into()));
+        assert_eq!(*counter.0.lock(), 1);
+    }
+}
@@ -0,0 +1,263 @@
+//! Intercepting Transport
+//!
+//! Allows to rewrite requests before they are sent
+//! and to inspect responses before they are decoded.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use futures::{Async, Future, Poll};
+use rpc;
+use transports::Result;
+use {BatchTransport, Error, RequestId, Transport};
+
+/// Request/response hooks.
+pub trait Interceptor: Send + Sync {
+    /// Called with every request before it is sent.
+    fn on_request(&self, _request: &mut rpc::Call) {}
+
+    /// Called with every successful response before it is decoded.
+    fn on_response(&self, _response: &rpc::Value) {}
+}
+
+/// Interceptor renaming methods, e.g. legacy `eth_*` methods to `cita_*`.
+#[derive(Debug, Default, Clone)]
+pub struct RenameMethods {
+    names: HashMap<String, String>,
+}
+
+impl RenameMethods {
+    /// Creates new interceptor without any renames.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends calls to method `from` as calls to method `to`.
+    pub fn rename<A: Into<String>, B: Into<String>>(mut self, from: A, to: B) -> Self {
+        self.names.insert(from.into(), to.into());
+        self
+    }
+}
+
+impl Interceptor for RenameMethods {
+    fn on_request(&self, request: &mut rpc::Call) {
+        let method = match *request {
+            rpc::Call::MethodCall(ref mut call) => &mut call.method,
+            rpc::Call::Notification(ref mut notification) => &mut notification.method,
+            rpc::Call::Invalid(_) => return,
+        };
+
+        if let Some(name) = self.names.get(method.as_str()) {
+            *method = name.clone();
+        }
+    }
+}
+
+type Interceptors = Arc<Vec<Arc<Interceptor>>>;
+
+/// Transport passing requests and responses through a chain of interceptors.
+///
+/// Requests are passed to interceptors in the order they were added,
+/// responses in the reverse order.
+#[derive(Clone)]
+pub struct Intercepted<T> {
+    transport: T,
+    interceptors: Interceptors,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Intercepted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Intercepted")
+            .field("transport", &self.transport)
+            .field("interceptors", &self.interceptors.len())
+            .finish()
+    }
+}
+
+impl<T: Transport> Intercepted<T> {
+    /// Creates new transport without any interceptors.
+    pub fn new(transport: T) -> Self {
+        Intercepted {
+            transport,
+            interceptors: Default::default(),
+        }
+    }
+
+    /// Adds interceptor at the end of the chain.
+    pub fn with<I: Interceptor + 'static>(mut self, interceptor: I) -> Self {
+        let mut interceptors = (*self.interceptors).clone();
+        interceptors.push(Arc::new(interceptor));
+        self.interceptors = Arc::new(interceptors);
+        self
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    fn intercept(&self, mut request: rpc::Call) -> rpc::Call {
+        for interceptor in self.interceptors.iter() {
+            interceptor.on_request(&mut request);
+        }
+        request
+    }
+}
+
+fn inspect(interceptors: &Interceptors, response: &rpc::Value) {
+    for interceptor in interceptors.iter().rev() {
+        interceptor.on_response(response);
+    }
+}
+
+impl<T: Transport> Transport for Intercepted<T> {
+    type Out = InterceptedCall<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        InterceptedCall {
+            inner: self.transport.send(id, self.intercept(request)),
+            interceptors: self.interceptors.clone(),
+        }
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Intercepted<T> {
+    type Batch = InterceptedBatch<T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let requests = requests
+            .into_iter()
+            .map(|(id, request)| (id, self.intercept(request)))
+            .collect::<Vec<_>>();
+
+        InterceptedBatch {
+            inner: self.transport.send_batch(requests),
+            interceptors: self.interceptors.clone(),
+        }
+    }
+}
+
+/// A future passing the response through interceptors.
+pub struct InterceptedCall<F> {
+    inner: F,
+    interceptors: Interceptors,
+}
+
+impl<F> Future for InterceptedCall<F>
+where
+    F: Future<Item = rpc::Value, Error = Error>,
+{
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let response = try_ready!(self.inner.poll());
+        inspect(&self.interceptors, &response);
+        Ok(Async::Ready(response))
+    }
+}
+
+/// A batch future passing all successful responses through interceptors.
+pub struct InterceptedBatch<F> {
+    inner: F,
+    interceptors: Interceptors,
+}
+
+impl<F> Future for InterceptedBatch<F>
+where
+    F: Future<Item = Vec<Result<rpc::Value>>, Error = Error>,
+{
+    type Item = Vec<Result<rpc::Value>>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let responses = try_ready!(self.inner.poll());
+        for response in &responses {
+            if let Ok(ref response) = *response {
+                inspect(&self.interceptors, response);
+            }
+        }
+        Ok(Async::Ready(responses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use futures::Future;
+    use parking_lot::Mutex;
+    use rpc::{self, Value};
+    use api::{Cita, Namespace};
+    use helpers::tests::TestTransport;
+    use transports::Batch;
+    use types::U256;
+    use super::{Intercepted, Interceptor, RenameMethods};
+
+    #[derive(Default)]
+    struct Log(Arc<Mutex<Vec<String>>>);
+
+    impl Interceptor for Log {
+        fn on_request(&self, request: &mut rpc::Call) {
+            if let rpc::Call::MethodCall(ref call) = *request {
+                self.0.lock().push(format!("request {}", call.method));
+            }
+        }
+
+        fn on_response(&self, response: &rpc::Value) {
+            self.0.lock().push(format!("response {}", response));
+        }
+    }
+
+    #[test]
+    fn should_rewrite_requests_and_inspect_responses() {
+        // given
+        let log = Log::default();
+        let entries = log.0.clone();
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x10".into()));
+        let transport = Intercepted::new(transport)
+            .with(RenameMethods::new().rename("cita_blockNumber", "eth_blockNumber"))
+            .with(log);
+
+        // when
+        let height = Cita::new(&transport).block_number().wait();
+
+        // then
+        assert_eq!(height, Ok(U256::from(0x10)));
+        assert_eq!(
+            *entries.lock(),
+            vec![
+                "request eth_blockNumber".to_owned(),
+                "response \"0x10\"".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_intercept_batches() {
+        // given
+        let log = Log::default();
+        let entries = log.0.clone();
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let batch = Batch::new(Intercepted::new(transport).with(log));
+
+        // when
+        let first = Cita::new(&batch).block_number();
+        let second = Cita::new(&batch).block_number();
+        batch.submit_batch().wait().unwrap();
+
+        // then
+        assert_eq!(first.wait(), Ok(U256::from(1)));
+        assert_eq!(second.wait(), Ok(U256::from(2)));
+        assert_eq!(entries.lock().len(), 4);
+    }
+}
@@ -2,10 +2,12 @@
 
 extern crate tokio_uds;
 
-use std::collections::BTreeMap;
+use std::{cmp, fmt, mem};
+use std::collections::{BTreeMap, VecDeque};
 use std::io::{self, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{atomic, Arc};
+use std::time::Duration;
 
 use self::tokio_uds::UnixStream;
 
@@ -14,8 +16,9 @@ use futures::{self, Future, Stream};
 use helpers;
 use parking_lot::Mutex;
 use rpc;
+use tokio_timer::{Sleep, Timer};
 use transports::Result;
-use transports::shared::{EventLoopHandle, Response};
+use transports::shared::{EventLoopHandle, RequestTimeout, Response};
 use transports::tokio_core::reactor;
 use transports::tokio_io::AsyncRead;
 use transports::tokio_io::io::{ReadHalf, WriteHalf};
@@ -34,16 +37,116 @@ macro_rules! try_nb {
   })
 }
 
+// Delay before the first reconnection attempt, doubled after every failure.
+const MIN_RECONNECT_DELAY_MS: u64 = 100;
+const MAX_RECONNECT_DELAY_MS: u64 = 30_000;
+/// Default number of failed reconnection attempts (about 25s) requests sent while disconnected wait for.
+pub const DEFAULT_MAX_QUEUED_ATTEMPTS: usize = 8;
+
 type Pending = oneshot::Sender<Result<Vec<Result<rpc::Value>>>>;
+type PendingRequests = Arc<Mutex<BTreeMap<RequestId, Pending>>>;
 
 /// A future representing pending IPC request, resolves to a response.
 pub type IpcTask<F> = Response<F, Vec<Result<rpc::Value>>>;
 
+/// State of the connection to the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Socket is connected and requests are being sent.
+    Connected,
+    /// Socket was closed, in-flight requests have been failed.
+    Disconnected,
+    /// Attempting to connect again (number of the attempt).
+    Reconnecting(usize),
+}
+
+/// Callback notified about connection state changes.
+pub type ConnectionListener = Arc<Fn(ConnectionState) + Send + Sync>;
+
+#[derive(Default)]
+struct Listener(Mutex<Option<ConnectionListener>>);
+
+impl Listener {
+    fn notify(&self, state: ConnectionState) {
+        // Release the lock before calling, the callback may replace itself.
+        let listener = self.0.lock().clone();
+        if let Some(listener) = listener {
+            listener(state);
+        }
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Listener({})", self.0.lock().is_some())
+    }
+}
+
+/// Builder of the IPC transport.
+#[derive(Debug, Clone)]
+pub struct IpcBuilder {
+    path: PathBuf,
+    timeout: Option<Duration>,
+    max_queued_attempts: usize,
+}
+
+impl IpcBuilder {
+    /// Creates a new builder for given socket path.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        IpcBuilder {
+            path: path.as_ref().to_owned(),
+            timeout: None,
+            max_queued_attempts: DEFAULT_MAX_QUEUED_ATTEMPTS,
+        }
+    }
+
+    /// Sets a default timeout for all requests (at most 20 minutes, longer timeouts are capped).
+    /// Requests not resolved in time fail with `ErrorKind::Timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets number of failed reconnection attempts requests sent while disconnected wait for.
+    /// Once exceeded, queued requests fail with `NotConnected` and so do new ones,
+    /// until the connection is re-established.
+    pub fn max_queued_attempts(mut self, attempts: usize) -> Self {
+        self.max_queued_attempts = attempts;
+        self
+    }
+
+    /// Create new IPC transport and spawn an event loop in a separate thread.
+    /// NOTE: Dropping event loop handle will stop the transport layer!
+    pub fn spawn(self) -> Result<(EventLoopHandle, Ipc)> {
+        EventLoopHandle::spawn(move |handle| self.build(handle))
+    }
+
+    /// Create new IPC transport within existing event loop.
+    pub fn build(self, handle: &reactor::Handle) -> Result<Ipc> {
+        trace!("Connecting to: {:?}", self.path);
+        let stream = UnixStream::connect(&self.path, handle)?;
+        let reconnect = Reconnect {
+            path: self.path,
+            handle: handle.clone(),
+            timer: Timer::default(),
+            attempt: 0,
+            max_queued_attempts: self.max_queued_attempts,
+        };
+        let ipc = Ipc::with_connection(stream, handle, Some(reconnect))?;
+        Ok(ipc.with_timeout(self.timeout))
+    }
+}
+
 /// Unix Domain Sockets (IPC) transport
+/// Reconnects automatically (with backoff) if the socket gets closed.
+/// Requests sent meanwhile are queued for a limited number of attempts (see `IpcBuilder::max_queued_attempts`).
 #[derive(Debug, Clone)]
 pub struct Ipc {
     id: Arc<atomic::AtomicUsize>,
-    pending: Arc<Mutex<BTreeMap<RequestId, Pending>>>,
+    pending: PendingRequests,
+    timeout: RequestTimeout,
+    connected: Arc<atomic::AtomicBool>,
+    listener: Arc<Listener>,
     write_sender: mpsc::UnboundedSender<Vec<u8>>,
 }
 
@@ -54,8 +157,7 @@ impl Ipc {
     where
         P: AsRef<Path>,
     {
-        let path = path.as_ref().to_owned();
-        EventLoopHandle::spawn(move |handle| Self::with_event_loop(&path, &handle).map_err(Into::into))
+        IpcBuilder::new(path).spawn()
     }
 
     /// Create new IPC transport within existing Event Loop.
@@ -63,40 +165,72 @@ impl Ipc {
     where
         P: AsRef<Path>,
     {
-        trace!("Connecting to: {:?}", path.as_ref());
-        let stream = UnixStream::connect(path, handle)?;
-        Self::with_stream(stream, handle)
+        IpcBuilder::new(path).build(handle)
     }
 
     /// Creates new IPC transport from existing `UnixStream` and `Handle`
+    /// The transport is not able to reconnect.
     fn with_stream(stream: UnixStream, handle: &reactor::Handle) -> Result<Self> {
-        let (read, write) = stream.split();
-        let (write_sender, write_receiver) = mpsc::unbounded();
-        let pending = Arc::new(Mutex::new(BTreeMap::new()));
+        Self::with_connection(stream, handle, None)
+    }
 
-        let r = ReadStream {
-            read,
-            pending: pending.clone(),
-            buffer: vec![],
-            current_pos: 0,
-        };
+    fn with_connection(stream: UnixStream, handle: &reactor::Handle, reconnect: Option<Reconnect>) -> Result<Self> {
+        let (write_sender, write_receiver) = mpsc::unbounded();
+        let pending = PendingRequests::default();
+        let connected = Arc::new(atomic::AtomicBool::new(true));
+        let listener = Arc::new(Listener::default());
 
-        let w = WriteStream {
-            write,
+        let connection = Connection {
+            state: ConnectionStatus::connected(stream, &pending),
             incoming: write_receiver,
-            state: WriteState::WaitingForRequest,
+            backlog: VecDeque::new(),
+            pending: pending.clone(),
+            connected: connected.clone(),
+            listener: listener.clone(),
+            reconnect,
         };
 
-        handle.spawn(r);
-        handle.spawn(w);
+        handle.spawn(connection);
 
         Ok(Ipc {
             id: Arc::new(atomic::AtomicUsize::new(1)),
             write_sender,
             pending,
+            timeout: RequestTimeout::default(),
+            connected,
+            listener,
         })
     }
 
+    /// Sets a callback invoked every time the connection state changes.
+    /// Replaces previously set callback (shared by all clones of the transport).
+    pub fn on_connection_change<F>(&self, listener: F)
+    where
+        F: Fn(ConnectionState) + Send + Sync + 'static,
+    {
+        *self.listener.0.lock() = Some(Arc::new(listener));
+    }
+
+    /// Returns `true` if the socket is currently connected.
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(atomic::Ordering::Acquire)
+    }
+
+    /// Returns a copy of this transport using different timeout for its requests
+    /// (at most 20 minutes, longer timeouts are capped).
+    /// Passing `None` disables the timeout. The copy shares the connection with the original.
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Self {
+        Ipc {
+            timeout: self.timeout.with_duration(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// Returns timeout applied to requests sent by this transport.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.duration()
+    }
+
     fn send_request<F, O>(&self, id: RequestId, request: rpc::Request, extract: F) -> IpcTask<F>
     where
         F: Fn(Vec<Result<rpc::Value>>) -> O,
@@ -111,7 +245,12 @@ impl Ipc {
             .unbounded_send(request.into_bytes())
             .map_err(|_| ErrorKind::Io(io::ErrorKind::BrokenPipe.into()).into());
 
+        let pending = self.pending.clone();
         Response::new(id, result, rx, extract)
+            .with_timeout(self.timeout.start())
+            .on_cancel(move || {
+                pending.lock().remove(&id);
+            })
     }
 }
 
@@ -153,6 +292,173 @@ impl BatchTransport for Ipc {
     }
 }
 
+enum ConnectionStatus {
+    Connected(ReadStream, WriteStream),
+    Waiting(Sleep),
+    Closed,
+}
+
+impl ConnectionStatus {
+    fn connected(stream: UnixStream, pending: &PendingRequests) -> Self {
+        let (read, write) = stream.split();
+        ConnectionStatus::Connected(ReadStream::new(read, pending), WriteStream::new(write))
+    }
+}
+
+/// Reconnection details, available only for connections established from a path.
+struct Reconnect {
+    path: PathBuf,
+    handle: reactor::Handle,
+    timer: Timer,
+    attempt: usize,
+    max_queued_attempts: usize,
+}
+
+impl Reconnect {
+    fn delay(&self) -> Duration {
+        let shift = cmp::min(self.attempt, 16) as u32;
+        let delay = MIN_RECONNECT_DELAY_MS.saturating_mul(1 << shift);
+        Duration::from_millis(cmp::min(delay, MAX_RECONNECT_DELAY_MS))
+    }
+
+    /// Returns `true` if requests should no longer wait for the connection.
+    fn gave_up_queuing(&self) -> bool {
+        self.attempt > self.max_queued_attempts
+    }
+}
+
+/// Fails all pending requests, returns the number of failed requests.
+fn fail_pending(pending: &PendingRequests, kind: io::ErrorKind) -> usize {
+    let pending = mem::replace(&mut *pending.lock(), BTreeMap::new());
+    let count = pending.len();
+    for (_, request) in pending {
+        let _ = request.send(Err(ErrorKind::Io(kind.into()).into()));
+    }
+    count
+}
+
+/// Drives reading and writing halves of the socket.
+/// Fails all in-flight requests when the socket gets closed and reconnects if possible.
+/// Stops once all transport handles are dropped, also while waiting to reconnect.
+struct Connection {
+    state: ConnectionStatus,
+    incoming: mpsc::UnboundedReceiver<Vec<u8>>,
+    // Requests sent while disconnected, written once reconnected.
+    backlog: VecDeque<Vec<u8>>,
+    pending: PendingRequests,
+    connected: Arc<atomic::AtomicBool>,
+    listener: Arc<Listener>,
+    reconnect: Option<Reconnect>,
+}
+
+impl Connection {
+    fn disconnected(&mut self) -> ConnectionStatus {
+        self.connected.store(false, atomic::Ordering::Release);
+        self.listener.notify(ConnectionState::Disconnected);
+
+        let failed = fail_pending(&self.pending, io::ErrorKind::BrokenPipe);
+        warn!("IPC connection lost, failing {} in-flight requests.", failed);
+
+        self.schedule_reconnect()
+    }
+
+    fn schedule_reconnect(&mut self) -> ConnectionStatus {
+        match self.reconnect {
+            Some(ref mut reconnect) => {
+                let delay = reconnect.delay();
+                reconnect.attempt += 1;
+                debug!("Reconnecting in {:?} (attempt {}).", delay, reconnect.attempt);
+                ConnectionStatus::Waiting(reconnect.timer.sleep(delay))
+            }
+            None => ConnectionStatus::Closed,
+        }
+    }
+
+    fn try_reconnect(&mut self) -> ConnectionStatus {
+        let result = match self.reconnect {
+            Some(ref reconnect) => {
+                self.listener
+                    .notify(ConnectionState::Reconnecting(reconnect.attempt));
+                trace!("Connecting to: {:?}", reconnect.path);
+                UnixStream::connect(&reconnect.path, &reconnect.handle)
+            }
+            None => return ConnectionStatus::Closed,
+        };
+
+        match result {
+            Ok(stream) => {
+                info!("IPC connection re-established.");
+                if let Some(ref mut reconnect) = self.reconnect {
+                    reconnect.attempt = 0;
+                }
+                self.connected.store(true, atomic::Ordering::Release);
+                self.listener.notify(ConnectionState::Connected);
+                ConnectionStatus::connected(stream, &self.pending)
+            }
+            Err(err) => {
+                warn!("Unable to reconnect: {:?}", err);
+                self.schedule_reconnect()
+            }
+        }
+    }
+}
+
+impl Future for Connection {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        loop {
+            let next_state = match self.state {
+                ConnectionStatus::Connected(ref mut read, ref mut write) => {
+                    let read = read.poll();
+                    let write = write.poll_write(&mut self.backlog, &mut self.incoming);
+                    match (read, write) {
+                        (_, Ok(futures::Async::Ready(()))) => {
+                            trace!("All transport handles dropped, closing IPC connection.");
+                            return Ok(futures::Async::Ready(()));
+                        }
+                        (Ok(futures::Async::NotReady), Ok(futures::Async::NotReady)) => {
+                            return Ok(futures::Async::NotReady);
+                        }
+                        _ => None,
+                    }
+                }
+                ConnectionStatus::Waiting(ref mut sleep) => {
+                    loop {
+                        match self.incoming.poll() {
+                            Ok(futures::Async::Ready(Some(request))) => self.backlog.push_back(request),
+                            Ok(futures::Async::NotReady) => break,
+                            Ok(futures::Async::Ready(None)) | Err(()) => {
+                                trace!("All transport handles dropped, no longer reconnecting.");
+                                return Ok(futures::Async::Ready(()));
+                            }
+                        }
+                    }
+
+                    let gave_up = self.reconnect.as_ref().map_or(false, Reconnect::gave_up_queuing);
+                    if gave_up && !self.backlog.is_empty() {
+                        self.backlog.clear();
+                        let failed = fail_pending(&self.pending, io::ErrorKind::NotConnected);
+                        warn!("IPC still disconnected, failing {} queued requests.", failed);
+                    }
+
+                    match sleep.poll() {
+                        Ok(futures::Async::NotReady) => return Ok(futures::Async::NotReady),
+                        _ => Some(()),
+                    }
+                }
+                ConnectionStatus::Closed => return Ok(futures::Async::Ready(())),
+            };
+
+            self.state = match next_state {
+                None => self.disconnected(),
+                Some(()) => self.try_reconnect(),
+            };
+        }
+    }
+}
+
 enum WriteState {
     WaitingForRequest,
     Writing { buffer: Vec<u8>, current_pos: usize },
@@ -162,20 +468,33 @@ enum WriteState {
 /// Awaits new requests using `mpsc::UnboundedReceiver` and writes them to the socket.
 struct WriteStream {
     write: WriteHalf<UnixStream>,
-    incoming: mpsc::UnboundedReceiver<Vec<u8>>,
     state: WriteState,
 }
 
-impl Future for WriteStream {
-    type Item = ();
-    type Error = ();
+impl WriteStream {
+    fn new(write: WriteHalf<UnixStream>) -> Self {
+        WriteStream {
+            write,
+            state: WriteState::WaitingForRequest,
+        }
+    }
 
-    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+    /// Writes requests queued in `backlog` and then incoming requests to the socket.
+    /// Resolves when there are no more requests (all transport handles were dropped),
+    /// fails if the socket can't be written to.
+    fn poll_write(
+        &mut self,
+        backlog: &mut VecDeque<Vec<u8>>,
+        incoming: &mut mpsc::UnboundedReceiver<Vec<u8>>,
+    ) -> futures::Poll<(), ()> {
         loop {
             self.state = match self.state {
                 WriteState::WaitingForRequest => {
                     // Ask for more to write
-                    let to_send = try_ready!(self.incoming.poll());
+                    let to_send = match backlog.pop_front() {
+                        Some(to_send) => Some(to_send),
+                        None => try_ready!(incoming.poll()),
+                    };
                     if let Some(to_send) = to_send {
                         trace!(
                             "Got new message to write: {:?}",
@@ -186,7 +505,7 @@ impl Future for WriteStream {
                             current_pos: 0,
                         }
                     } else {
-                        return Ok(futures::Async::NotReady);
+                        return Ok(futures::Async::Ready(()));
                     }
                 }
                 WriteState::Writing {
@@ -211,9 +530,10 @@ impl Future for WriteStream {
 }
 /// Reading part of the IPC transport.
 /// Reads data on the socket and tries to dispatch it to awaiting requests.
+/// Fails when the socket gets closed.
 struct ReadStream {
     read: ReadHalf<UnixStream>,
-    pending: Arc<Mutex<BTreeMap<RequestId, Pending>>>,
+    pending: PendingRequests,
     buffer: Vec<u8>,
     current_pos: usize,
 }
@@ -235,7 +555,8 @@ impl Future for ReadStream {
 
             let read = try_nb!(self.read.read(&mut self.buffer[self.current_pos..]));
             if read == 0 {
-                return Ok(futures::Async::NotReady);
+                warn!("IPC socket closed by the remote end.");
+                return Err(());
             }
 
             let mut min = self.current_pos;
@@ -262,6 +583,15 @@ impl Future for ReadStream {
 }
 
 impl ReadStream {
+    fn new(read: ReadHalf<UnixStream>, pending: &PendingRequests) -> Self {
+        ReadStream {
+            read,
+            pending: pending.clone(),
+            buffer: vec![],
+            current_pos: 0,
+        }
+    }
+
     fn respond(&self, outputs: Vec<rpc::Output>) {
         let id = match outputs.get(0) {
             Some(&rpc::Output::Success(ref success)) => success.id.clone(),
@@ -307,11 +637,38 @@ mod tests {
     extern crate tokio_core;
     extern crate tokio_uds;
 
-    use std::io::{Read, Write};
-    use super::Ipc;
+    use std::{env, fs, process, thread};
+    use std::io::{self, Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use super::{ConnectionState, Ipc, IpcBuilder};
     use futures::{self, Future};
+    use parking_lot::Mutex;
     use rpc;
-    use Transport;
+    use {ErrorKind, Transport};
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("web3-ipc-{}-{}.sock", name, process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn record_states(ipc: &Ipc) -> Arc<Mutex<Vec<ConnectionState>>> {
+        let states = Arc::new(Mutex::new(vec![]));
+        let recorded = states.clone();
+        ipc.on_connection_change(move |state| recorded.lock().push(state));
+        states
+    }
+
+    fn turn_until<F: Fn() -> bool>(eloop: &mut tokio_core::reactor::Core, condition: F) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "Condition not met in time.");
+            eloop.turn(Some(Duration::from_millis(10)));
+        }
+    }
 
     #[test]
     fn should_send_a_request() {
@@ -406,4 +763,197 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn should_time_out_and_clean_up_pending_request() {
+        // given
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let handle = eloop.handle();
+        let (_server, client) = tokio_uds::UnixStream::pair(&handle).unwrap();
+        let ipc = Ipc::with_stream(client, &handle)
+            .unwrap()
+            .with_timeout(Some(Duration::from_millis(200)));
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+
+        // then
+        assert_eq!(eloop.run(res), Err(ErrorKind::Timeout.into()));
+        assert!(ipc.pending.lock().is_empty());
+    }
+
+    #[test]
+    fn should_apply_default_timeout_from_builder() {
+        // given
+        let path = socket_path("timeout");
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let ipc = IpcBuilder::new(&path)
+            .timeout(Duration::from_millis(200))
+            .build(&eloop.handle())
+            .unwrap();
+        let _server = listener.accept().unwrap();
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+
+        // then
+        assert_eq!(ipc.timeout(), Some(Duration::from_millis(200)));
+        assert_eq!(eloop.run(res), Err(ErrorKind::Timeout.into()));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_clean_up_dropped_request() {
+        // given
+        let eloop = tokio_core::reactor::Core::new().unwrap();
+        let handle = eloop.handle();
+        let (_server, client) = tokio_uds::UnixStream::pair(&handle).unwrap();
+        let ipc = Ipc::with_stream(client, &handle).unwrap();
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+        assert_eq!(ipc.pending.lock().len(), 1);
+        drop(res);
+
+        // then
+        assert!(ipc.pending.lock().is_empty());
+    }
+
+    #[test]
+    fn should_fail_in_flight_requests_when_socket_is_closed() {
+        // given
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let handle = eloop.handle();
+        let (server, client) = tokio_uds::UnixStream::pair(&handle).unwrap();
+        let ipc = Ipc::with_stream(client, &handle).unwrap();
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+        drop(server);
+
+        // then
+        assert_eq!(
+            eloop.run(res),
+            Err(ErrorKind::Io(io::ErrorKind::BrokenPipe.into()).into())
+        );
+        assert!(!ipc.is_connected());
+        assert!(ipc.pending.lock().is_empty());
+    }
+
+    #[test]
+    fn should_reconnect_and_send_later_requests() {
+        // given
+        let path = socket_path("reconnect");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            // Close the first connection right away.
+            drop(listener.accept().unwrap());
+
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut data = [0; 2048];
+            let read = stream.read(&mut data).unwrap();
+            let request = String::from_utf8(data[0..read].to_vec()).unwrap();
+            assert_eq!(&request, r#"{"jsonrpc":"2.0","method":"eth_accounts","params":[],"id":1}"#);
+            stream
+                .write_all(br#"{"jsonrpc":"2.0","id":1,"result":"x"}"#)
+                .unwrap();
+        });
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let ipc = Ipc::with_event_loop(&path, &eloop.handle()).unwrap();
+        let states = record_states(&ipc);
+        turn_until(&mut eloop, || !ipc.is_connected());
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+
+        // then
+        assert_eq!(eloop.run(res), Ok(rpc::Value::String("x".into())));
+        assert!(ipc.is_connected());
+        assert_eq!(
+            *states.lock(),
+            vec![
+                ConnectionState::Disconnected,
+                ConnectionState::Reconnecting(1),
+                ConnectionState::Connected,
+            ]
+        );
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_fail_queued_requests_when_reconnecting_fails() {
+        // given
+        let path = socket_path("unreachable");
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let ipc = IpcBuilder::new(&path)
+            .max_queued_attempts(1)
+            .build(&eloop.handle())
+            .unwrap();
+        drop(listener.accept().unwrap());
+        drop(listener);
+        turn_until(&mut eloop, || !ipc.is_connected());
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+
+        // then
+        assert_eq!(
+            eloop.run(res),
+            Err(ErrorKind::Io(io::ErrorKind::NotConnected.into()).into())
+        );
+        assert!(ipc.pending.lock().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_stop_reconnecting_when_transport_is_dropped() {
+        // given
+        let path = socket_path("dropped");
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let ipc = Ipc::with_event_loop(&path, &eloop.handle()).unwrap();
+        let states = record_states(&ipc);
+        drop(listener.accept().unwrap());
+        turn_until(&mut eloop, || !ipc.is_connected());
+
+        // when
+        drop(ipc);
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while Instant::now() < deadline {
+            eloop.turn(Some(Duration::from_millis(10)));
+        }
+
+        // then
+        assert_eq!(*states.lock(), vec![ConnectionState::Disconnected]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_allow_replacing_listener_from_callback() {
+        // given
+        let mut eloop = tokio_core::reactor::Core::new().unwrap();
+        let handle = eloop.handle();
+        let (server, client) = tokio_uds::UnixStream::pair(&handle).unwrap();
+        let ipc = Ipc::with_stream(client, &handle).unwrap();
+        let states = Arc::new(Mutex::new(vec![]));
+        {
+            let ipc2 = ipc.clone();
+            let states = states.clone();
+            ipc.on_connection_change(move |state| {
+                states.lock().push(state);
+                ipc2.on_connection_change(|_| {});
+            });
+        }
+
+        // when
+        let res = ipc.execute("eth_accounts", vec![]);
+        drop(server);
+
+        // then
+        assert!(eloop.run(res).is_err());
+        assert_eq!(*states.lock(), vec![ConnectionState::Disconnected]);
+    }
 }
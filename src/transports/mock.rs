@@ -0,0 +1,320 @@
+//! Mock Transport
+//!
+//! Scripted transport for unit tests of code using `Web3`.
+//! Responses are registered per method (and optionally parameters)
+//! and all requests are recorded so they can be asserted afterwards.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use futures::future;
+use parking_lot::Mutex;
+use rpc;
+use serde;
+use transports::Result;
+use {helpers, BatchTransport, ErrorKind, RequestId, Transport};
+
+/// A single scripted response.
+#[derive(Debug, Clone)]
+struct Scripted {
+    method: String,
+    params: Option<Vec<rpc::Value>>,
+    response: Result<rpc::Value>,
+}
+
+impl Scripted {
+    fn matches(&self, method: &str, params: &[rpc::Value]) -> bool {
+        self.method == method && self.params.as_ref().map_or(true, |p| &**p == params)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    id: RequestId,
+    scripted: Vec<Scripted>,
+    queue: VecDeque<Result<rpc::Value>>,
+    requests: Vec<(String, Vec<rpc::Value>)>,
+    batches: Vec<usize>,
+    asserted: usize,
+}
+
+impl Inner {
+    fn respond(&mut self, method: &str, params: &[rpc::Value]) -> Result<rpc::Value> {
+        self.requests.push((method.to_owned(), params.to_vec()));
+
+        // Responses for exact parameters take precedence over method-only ones.
+        let position = self.scripted
+            .iter()
+            .position(|s| s.params.is_some() && s.matches(method, params))
+            .or_else(|| self.scripted.iter().position(|s| s.matches(method, params)));
+
+        match position {
+            Some(position) => self.scripted.remove(position).response,
+            None => match self.queue.pop_front() {
+                Some(response) => response,
+                None => Err(ErrorKind::Transport(format!(
+                    "Unexpected request: {} with params {:?}",
+                    method,
+                    helpers::to_string(&params)
+                )).into()),
+            },
+        }
+    }
+}
+
+/// Transport returning scripted responses.
+///
+/// Clones share scripted responses and recorded requests.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MockTransport {
+    /// Creates new mock transport without any responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Responds to the next call of `method` (with any parameters).
+    pub fn respond<T: serde::Serialize>(&self, method: &str, value: T) -> &Self {
+        self.script(method, None, Ok(helpers::serialize(&value)))
+    }
+
+    /// Responds to the next call of `method` with exactly given parameters.
+    pub fn respond_with_params<T: serde::Serialize>(&self, method: &str, params: Vec<rpc::Value>, value: T) -> &Self {
+        self.script(method, Some(params), Ok(helpers::serialize(&value)))
+    }
+
+    /// Fails the next call of `method` (with any parameters) with given RPC error.
+    pub fn fail(&self, method: &str, error: rpc::Error) -> &Self {
+        self.script(method, None, Err(ErrorKind::Rpc(error).into()))
+    }
+
+    /// Fails the next call of `method` with exactly given parameters.
+    pub fn fail_with_params(&self, method: &str, params: Vec<rpc::Value>, error: rpc::Error) -> &Self {
+        self.script(method, Some(params), Err(ErrorKind::Rpc(error).into()))
+    }
+
+    /// Queues a response returned for the next request not matching any scripted method.
+    pub fn push_response<T: serde::Serialize>(&self, value: T) -> &Self {
+        self.inner
+            .lock()
+            .queue
+            .push_back(Ok(helpers::serialize(&value)));
+        self
+    }
+
+    fn script(&self, method: &str, params: Option<Vec<rpc::Value>>, response: Result<rpc::Value>) -> &Self {
+        self.inner.lock().scripted.push(Scripted {
+            method: method.to_owned(),
+            params,
+            response,
+        });
+        self
+    }
+
+    /// Returns all requests received so far.
+    pub fn requests(&self) -> Vec<(String, Vec<rpc::Value>)> {
+        self.inner.lock().requests.clone()
+    }
+
+    /// Returns sizes of all batches received so far.
+    pub fn batches(&self) -> Vec<usize> {
+        self.inner.lock().batches.clone()
+    }
+
+    /// Returns `true` if all scripted and queued responses were used.
+    pub fn is_exhausted(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.scripted.is_empty() && inner.queue.is_empty()
+    }
+
+    /// Asserts that the next not yet asserted request was a call to `method` with `params`.
+    pub fn assert_request(&self, method: &str, params: &[rpc::Value]) {
+        let mut inner = self.inner.lock();
+        let idx = inner.asserted;
+        inner.asserted += 1;
+
+        let (ref m, ref p) = *inner
+            .requests
+            .get(idx)
+            .unwrap_or_else(|| panic!("Expected request to {}, got no more requests.", method));
+        assert_eq!(m, method, "Unexpected method of request #{}", idx);
+        assert_eq!(&**p, params, "Unexpected params of request #{} ({})", idx, method);
+    }
+
+    /// Asserts that the next not yet asserted request was a call to `method` with any params.
+    pub fn assert_method(&self, method: &str) {
+        let mut inner = self.inner.lock();
+        let idx = inner.asserted;
+        inner.asserted += 1;
+
+        let m = inner
+            .requests
+            .get(idx)
+            .map(|r| r.0.clone())
+            .unwrap_or_else(|| panic!("Expected request to {}, got no more requests.", method));
+        assert_eq!(m, method, "Unexpected method of request #{}", idx);
+    }
+
+    /// Asserts that all received requests were already asserted.
+    pub fn assert_no_more_requests(&self) {
+        let inner = self.inner.lock();
+        assert_eq!(
+            inner.asserted,
+            inner.requests.len(),
+            "Expected no more requests, got: {:?}",
+            &inner.requests[inner.asserted..]
+        );
+    }
+}
+
+/// Extracts method name and parameters from a prepared call.
+pub(crate) fn method_and_params(request: &rpc::Call) -> (String, Vec<rpc::Value>) {
+    match *request {
+        rpc::Call::MethodCall(ref call) => (call.method.clone(), params_vec(&call.params)),
+        rpc::Call::Notification(ref notification) => (notification.method.clone(), params_vec(&notification.params)),
+        rpc::Call::Invalid(_) => ("".into(), vec![]),
+    }
+}
+
+fn params_vec(params: &Option<rpc::Params>) -> Vec<rpc::Value> {
+    match *params {
+        Some(rpc::Params::Array(ref params)) => params.clone(),
+        Some(rpc::Params::Map(ref map)) => vec![rpc::Value::Object(map.clone())],
+        Some(rpc::Params::None) | None => vec![],
+    }
+}
+
+impl Transport for MockTransport {
+    type Out = ::Result<rpc::Value>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        let id = {
+            let mut inner = self.inner.lock();
+            inner.id += 1;
+            inner.id
+        };
+        (id, helpers::build_request(id, method, params))
+    }
+
+    fn send(&self, _id: RequestId, request: rpc::Call) -> Self::Out {
+        let (method, params) = method_and_params(&request);
+        Box::new(future::result(self.inner.lock().respond(&method, &params)))
+    }
+}
+
+impl BatchTransport for MockTransport {
+    type Batch = ::Result<Vec<Result<rpc::Value>>>;
+
+    fn send_batch<T>(&self, requests: T) -> Self::Batch
+    where
+        T: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let mut inner = self.inner.lock();
+        let results = requests
+            .into_iter()
+            .map(|(_, request)| {
+                let (method, params) = method_and_params(&request);
+                inner.respond(&method, &params)
+            })
+            .collect::<Vec<_>>();
+        inner.batches.push(results.len());
+
+        Box::new(future::ok(results))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::Future;
+    use rpc::{self, Value};
+    use api::Namespace;
+    use api::{Cita, Net};
+    use transports::Batch;
+    use types::{BlockNumber, U256};
+    use {ErrorKind, Transport};
+    use super::MockTransport;
+
+    #[test]
+    fn should_respond_by_method() {
+        // given
+        let mock = MockTransport::new();
+        mock.respond("net_peerCount", "0x2")
+            .respond("cita_blockNumber", "0x10");
+
+        // when
+        let height = Cita::new(&mock).block_number().wait();
+        let peers = Net::new(&mock).peer_count().wait();
+
+        // then
+        assert_eq!(height, Ok(U256::from(0x10)));
+        assert_eq!(peers, Ok("0x2".to_owned()));
+        mock.assert_request("cita_blockNumber", &[]);
+        mock.assert_method("net_peerCount");
+        mock.assert_no_more_requests();
+        assert!(mock.is_exhausted());
+    }
+
+    #[test]
+    fn should_prefer_responses_with_matching_params() {
+        // given
+        let mock = MockTransport::new();
+        mock.respond("eth_getCode", "0x00")
+            .respond_with_params(
+                "eth_getCode",
+                vec![
+                    Value::String("0x0000000000000000000000000000000000000123".into()),
+                    Value::String("0x1".into()),
+                ],
+                "0x0123",
+            );
+
+        // when
+        let code = Cita::new(&mock)
+            .code(0x123.into(), Some(BlockNumber::Number(1)))
+            .wait()
+            .unwrap();
+
+        // then
+        assert_eq!(code.0, vec![0x01, 0x23]);
+        assert!(!mock.is_exhausted());
+    }
+
+    #[test]
+    fn should_fail_unexpected_and_failing_requests() {
+        // given
+        let mock = MockTransport::new();
+        mock.fail("cita_blockNumber", rpc::Error::internal_error());
+
+        // when
+        let failed = mock.execute("cita_blockNumber", vec![]).wait();
+        let unexpected = mock.execute("cita_blockNumber", vec![]).wait();
+
+        // then
+        assert_eq!(failed, Err(ErrorKind::Rpc(rpc::Error::internal_error()).into()));
+        match unexpected.unwrap_err().kind() {
+            &ErrorKind::Transport(_) => {}
+            other => panic!("Unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_support_batches() {
+        // given
+        let mock = MockTransport::new();
+        mock.push_response("0x1").push_response("0x2");
+        let batch = Batch::new(mock.clone());
+
+        // when
+        let first = Cita::new(&batch).block_number();
+        let second = Cita::new(&batch).block_number();
+        batch.submit_batch().wait().unwrap();
+
+        // then
+        assert_eq!(first.wait(), Ok(U256::from(1)));
+        assert_eq!(second.wait(), Ok(U256::from(2)));
+        assert_eq!(mock.batches(), vec![2]);
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
@@ -5,18 +5,41 @@ use Error;
 /// RPC Result.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
+pub mod auto_batch;
+pub use self::auto_batch::AutoBatch;
 pub mod batch;
 pub use self::batch::Batch;
+pub mod cached;
+pub use self::cached::Cached;
+pub mod coalesced;
+pub use self::coalesced::Coalesced;
+pub mod intercepted;
+pub use self::intercepted::{Intercepted, Interceptor};
+pub mod instrumented;
+pub use self::instrumented::Instrumented;
+pub mod rate_limited;
+pub use self::rate_limited::{RateLimit, RateLimited};
+mod timer;
+pub use self::timer::MAX_REQUEST_TIMEOUT_SECS;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "mock")]
+pub use self::mock::MockTransport;
+#[cfg(feature = "mock")]
+pub mod replay;
+#[cfg(feature = "mock")]
+pub use self::replay::{Recorder, Replay, ReplayMode};
 
 #[cfg(feature = "http")]
 pub mod http;
 #[cfg(feature = "http")]
-pub use self::http::Http;
+pub use self::http::{Http, HttpBuilder};
 
 #[cfg(feature = "ipc")]
 pub mod ipc;
 #[cfg(feature = "ipc")]
-pub use self::ipc::Ipc;
+pub use self::ipc::{ConnectionState, Ipc, IpcBuilder};
 
 #[cfg(any(feature = "ipc", feature = "http"))]
 mod shared;
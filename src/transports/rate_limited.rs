@@ -0,0 +1,551 @@
+//! Rate Limited Transport
+//!
+//! Token bucket limiting the rate of requests sent by the inner transport.
+//! Reads and transaction submissions are limited separately,
+//! requests exceeding the limit are queued until tokens are available.
+//! Tokens are reserved when a request is sent, so queued requests go out in order
+//! and a batch can't be starved by a steady stream of single calls.
+
+use std::{cmp, fmt};
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicUsize};
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Poll};
+use parking_lot::Mutex;
+use rpc;
+use tokio_timer::{self, Sleep, Timer};
+use transports::Result;
+use {BatchTransport, Error, ErrorKind, RequestId, Transport};
+
+/// Class of a method, each class is limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodClass {
+    /// Queries not changing the state of the chain.
+    Read,
+    /// Transaction submissions.
+    Submission,
+}
+
+impl MethodClass {
+    /// Default classification: methods sending transactions are submissions.
+    pub fn of(method: &str) -> Self {
+        match method {
+            "cita_sendTransaction"
+            | "cita_sendRawTransaction"
+            | "eth_sendTransaction"
+            | "eth_sendRawTransaction"
+            | "personal_sendTransaction" => MethodClass::Submission,
+            _ => MethodClass::Read,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match *self {
+            MethodClass::Read => 0,
+            MethodClass::Submission => 1,
+        }
+    }
+}
+
+/// Limit of a single method class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained number of requests per second.
+    pub per_second: f64,
+    /// Maximal number of requests sent at once after being idle.
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Creates new limit.
+    ///
+    /// # Panics
+    ///
+    /// If `per_second` is not a positive finite number or `burst` is zero.
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        let limit = RateLimit { per_second, burst };
+        limit.check();
+        limit
+    }
+
+    fn check(&self) {
+        assert!(
+            self.per_second > 0.0 && self.per_second.is_finite(),
+            "Rate limit has to be a positive finite number of requests per second, got {}",
+            self.per_second
+        );
+        assert!(self.burst > 0, "Rate limit burst has to allow at least one request");
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Bucket {
+            limit,
+            tokens: limit.burst as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.updated);
+        let elapsed = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9;
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst as f64);
+        self.updated = now;
+    }
+
+    /// Returns how long to wait until `cost` tokens are available.
+    /// Requests costing more than the burst are allowed once the bucket is full.
+    /// Tokens may be negative (reserved by requests still waiting), which delays later requests.
+    fn wait_time(&self, cost: u32) -> Option<Duration> {
+        let needed = (cost as f64).min(self.limit.burst as f64);
+        if self.tokens >= needed {
+            return None;
+        }
+
+        let secs = (needed - self.tokens) / self.limit.per_second;
+        Some(Duration::new(secs as u64, (secs.fract() * 1e9) as u32))
+    }
+}
+
+#[derive(Debug)]
+struct Buckets([Bucket; 2]);
+
+impl Buckets {
+    /// Takes tokens for given costs (per class), going into debt if necessary.
+    /// Returns time to wait before the request may be sent.
+    fn acquire(&mut self, costs: &[u32; 2], now: Instant) -> Option<Duration> {
+        let mut wait = None;
+        for (bucket, &cost) in self.0.iter_mut().zip(costs.iter()) {
+            if cost == 0 {
+                continue;
+            }
+            bucket.refill(now);
+            if let Some(time) = bucket.wait_time(cost) {
+                wait = Some(wait.map_or(time, |w: Duration| w.max(time)));
+            }
+            bucket.tokens -= cost as f64;
+        }
+        wait
+    }
+
+    /// Returns tokens of a request which was dropped before being sent.
+    fn release(&mut self, costs: &[u32; 2]) {
+        for (bucket, &cost) in self.0.iter_mut().zip(costs.iter()) {
+            bucket.tokens = (bucket.tokens + cost as f64).min(bucket.limit.burst as f64);
+        }
+    }
+}
+
+// Longer waits are split, so that they don't exceed the range of the timer.
+const MAX_SLEEP_MS: u64 = 1_000;
+
+type Classifier = Arc<Fn(&str) -> MethodClass + Send + Sync>;
+
+#[derive(Clone)]
+struct Limiter {
+    buckets: Arc<Mutex<Buckets>>,
+    classifier: Classifier,
+    queued: Arc<AtomicUsize>,
+    timer: Timer,
+}
+
+impl fmt::Debug for Limiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Limiter")
+            .field("buckets", &self.buckets)
+            .field("queued", &self.queued)
+            .finish()
+    }
+}
+
+impl Limiter {
+    fn costs<'a, I: IntoIterator<Item = &'a rpc::Call>>(&self, requests: I) -> [u32; 2] {
+        let mut costs = [0; 2];
+        for request in requests {
+            let method = match *request {
+                rpc::Call::MethodCall(ref call) => &*call.method,
+                rpc::Call::Notification(ref notification) => &*notification.method,
+                rpc::Call::Invalid(_) => "",
+            };
+            costs[(self.classifier)(method).index()] += 1;
+        }
+        costs
+    }
+}
+
+/// Transport limiting the rate of requests.
+#[derive(Debug, Clone)]
+pub struct RateLimited<T> {
+    transport: T,
+    limiter: Limiter,
+}
+
+impl<T: Transport> RateLimited<T> {
+    /// Creates new rate limited transport with separate limits for reads and submissions.
+    ///
+    /// # Panics
+    ///
+    /// If any of the limits is invalid (see `RateLimit::new`).
+    pub fn new(transport: T, reads: RateLimit, submissions: RateLimit) -> Self {
+        reads.check();
+        submissions.check();
+        let now = Instant::now();
+        RateLimited {
+            transport,
+            limiter: Limiter {
+                buckets: Arc::new(Mutex::new(Buckets([Bucket::new(reads, now), Bucket::new(submissions, now)]))),
+                classifier: Arc::new(MethodClass::of),
+                queued: Default::default(),
+                timer: tokio_timer::wheel()
+                    .tick_duration(Duration::from_millis(10))
+                    .build(),
+            },
+        }
+    }
+
+    /// Uses custom function to classify methods.
+    pub fn with_classifier<F>(mut self, classifier: F) -> Self
+    where
+        F: Fn(&str) -> MethodClass + Send + Sync + 'static,
+    {
+        self.limiter.classifier = Arc::new(classifier);
+        self
+    }
+
+    /// Returns number of requests currently waiting for tokens.
+    pub fn queue_depth(&self) -> usize {
+        self.limiter.queued.load(atomic::Ordering::Acquire)
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+/// Reserves tokens for the request and waits until it may be sent.
+struct Permit {
+    limiter: Limiter,
+    costs: [u32; 2],
+    // Time the request may be sent at if it had to be queued.
+    ready_at: Option<Instant>,
+    sleep: Option<Sleep>,
+    done: bool,
+}
+
+impl Permit {
+    fn new(limiter: Limiter, costs: [u32; 2]) -> Self {
+        let now = Instant::now();
+        let ready_at = limiter.buckets.lock().acquire(&costs, now).map(|wait| {
+            trace!("Rate limit exceeded, waiting {:?}.", wait);
+            limiter.queued.fetch_add(1, atomic::Ordering::AcqRel);
+            now + wait
+        });
+
+        Permit {
+            limiter,
+            costs,
+            ready_at,
+            sleep: None,
+            done: false,
+        }
+    }
+
+    fn poll(&mut self) -> Poll<(), Error> {
+        loop {
+            if let Some(ref mut sleep) = self.sleep {
+                match sleep.poll() {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(())) => {}
+                    Err(err) => {
+                        return Err(ErrorKind::Transport(format!("Rate limiter timer failed: {:?}", err)).into());
+                    }
+                }
+            }
+
+            let now = Instant::now();
+            match self.ready_at {
+                Some(ready_at) if ready_at > now => {
+                    let wait = cmp::min(ready_at - now, Duration::from_millis(MAX_SLEEP_MS));
+                    self.sleep = Some(self.limiter.timer.sleep(wait));
+                }
+                _ => {
+                    self.dequeue();
+                    self.sleep = None;
+                    self.done = true;
+                    return Ok(Async::Ready(()));
+                }
+            }
+        }
+    }
+
+    fn dequeue(&mut self) {
+        if self.ready_at.take().is_some() {
+            self.limiter.queued.fetch_sub(1, atomic::Ordering::AcqRel);
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.dequeue();
+        if !self.done {
+            self.limiter.buckets.lock().release(&self.costs);
+        }
+    }
+}
+
+enum ThrottledState<R, F> {
+    Waiting(Permit, Option<R>),
+    Sending(F),
+}
+
+/// A future sending the request once the rate limit allows it.
+pub struct Throttled<T: Transport> {
+    transport: T,
+    state: ThrottledState<(RequestId, rpc::Call), T::Out>,
+}
+
+impl<T: Transport> Transport for RateLimited<T> {
+    type Out = Throttled<T>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        let costs = self.limiter.costs(Some(&request));
+        Throttled {
+            transport: self.transport.clone(),
+            state: ThrottledState::Waiting(Permit::new(self.limiter.clone(), costs), Some((id, request))),
+        }
+    }
+}
+
+impl<T: Transport> Future for Throttled<T> {
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let next_state = match self.state {
+                ThrottledState::Waiting(ref mut permit, ref mut request) => {
+                    try_ready!(permit.poll());
+                    let (id, request) = request.take().expect("request is sent only once; qed");
+                    ThrottledState::Sending(self.transport.send(id, request))
+                }
+                ThrottledState::Sending(ref mut future) => return future.poll(),
+            };
+            self.state = next_state;
+        }
+    }
+}
+
+/// A batch sent once the rate limit allows all of its requests.
+pub struct ThrottledBatch<T: BatchTransport> {
+    transport: T,
+    state: ThrottledState<Vec<(RequestId, rpc::Call)>, T::Batch>,
+}
+
+impl<T: BatchTransport> BatchTransport for RateLimited<T> {
+    type Batch = ThrottledBatch<T>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let requests = requests.into_iter().collect::<Vec<_>>();
+        let costs = self.limiter.costs(requests.iter().map(|r| &r.1));
+        ThrottledBatch {
+            transport: self.transport.clone(),
+            state: ThrottledState::Waiting(Permit::new(self.limiter.clone(), costs), Some(requests)),
+        }
+    }
+}
+
+impl<T: BatchTransport> Future for ThrottledBatch<T> {
+    type Item = Vec<Result<rpc::Value>>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let next_state = match self.state {
+                ThrottledState::Waiting(ref mut permit, ref mut requests) => {
+                    try_ready!(permit.poll());
+                    let requests = requests.take().expect("batch is sent only once; qed");
+                    ThrottledState::Sending(self.transport.send_batch(requests))
+                }
+                ThrottledState::Sending(ref mut future) => return future.poll(),
+            };
+            self.state = next_state;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use futures::Future;
+    use rpc::Value;
+    use helpers::tests::TestTransport;
+    use {BatchTransport, Transport};
+    use super::{Bucket, Buckets, MethodClass, RateLimit, RateLimited};
+
+    #[test]
+    fn should_classify_methods() {
+        assert_eq!(MethodClass::of("cita_sendTransaction"), MethodClass::Submission);
+        assert_eq!(MethodClass::of("eth_sendRawTransaction"), MethodClass::Submission);
+        assert_eq!(MethodClass::of("cita_blockNumber"), MethodClass::Read);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive finite number")]
+    fn should_reject_zero_rate() {
+        RateLimit::new(0.0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive finite number")]
+    fn should_reject_negative_rate_set_directly() {
+        let transport = TestTransport::default();
+        let invalid = RateLimit {
+            per_second: -1.0,
+            burst: 1,
+        };
+
+        RateLimited::new(&transport, RateLimit::new(1.0, 1), invalid);
+    }
+
+    #[test]
+    fn should_reject_invalid_limits() {
+        use std::f64;
+        use std::panic::catch_unwind;
+
+        assert!(catch_unwind(|| RateLimit::new(f64::NAN, 1)).is_err());
+        assert!(catch_unwind(|| RateLimit::new(f64::INFINITY, 1)).is_err());
+        assert!(catch_unwind(|| RateLimit::new(1.0, 0)).is_err());
+        assert_eq!(RateLimit::new(0.5, 1).per_second, 0.5);
+    }
+
+    #[test]
+    fn should_refill_buckets() {
+        let now = Instant::now();
+        let mut buckets = Buckets([
+            Bucket::new(RateLimit::new(10.0, 2), now),
+            Bucket::new(RateLimit::new(1.0, 1), now),
+        ]);
+
+        assert_eq!(buckets.acquire(&[2, 0], now), None);
+        assert_eq!(buckets.acquire(&[1, 0], now), Some(Duration::from_millis(100)));
+        // the token refilled by then is already reserved by the previous request
+        assert_eq!(buckets.acquire(&[1, 0], now + Duration::from_millis(200)), None);
+        // submissions are limited separately
+        assert_eq!(buckets.acquire(&[0, 1], now), None);
+        assert_eq!(buckets.acquire(&[0, 1], now), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn should_allow_batches_larger_than_burst_when_bucket_is_full() {
+        let now = Instant::now();
+        let mut buckets = Buckets([
+            Bucket::new(RateLimit::new(10.0, 2), now),
+            Bucket::new(RateLimit::new(1.0, 1), now),
+        ]);
+
+        assert_eq!(buckets.acquire(&[5, 0], now), None);
+        assert_eq!(buckets.acquire(&[1, 0], now), Some(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn should_queue_requests_exceeding_the_limit() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let limited = RateLimited::new(&transport, RateLimit::new(20.0, 1), RateLimit::new(1.0, 1));
+
+        // when
+        let started = Instant::now();
+        let first = limited.execute("cita_blockNumber", vec![]).wait();
+        let second = limited.execute("cita_blockNumber", vec![]).wait();
+
+        // then
+        assert_eq!(first, Ok(Value::String("0x1".into())));
+        assert_eq!(second, Ok(Value::String("0x2".into())));
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(limited.queue_depth(), 0);
+    }
+
+    #[test]
+    fn should_not_starve_batches_queued_before_single_calls() {
+        let now = Instant::now();
+        let mut buckets = Buckets([
+            Bucket::new(RateLimit::new(10.0, 2), now),
+            Bucket::new(RateLimit::new(1.0, 1), now),
+        ]);
+        assert_eq!(buckets.acquire(&[2, 0], now), None);
+
+        let batch = buckets.acquire(&[4, 0], now);
+        let single = buckets.acquire(&[1, 0], now + Duration::from_millis(100));
+
+        assert_eq!(batch, Some(Duration::from_millis(200)));
+        // a single call sent while the batch waits is queued after it
+        assert_eq!(single, Some(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn should_send_mixed_load_in_order() {
+        // given
+        let mut transport = TestTransport::default();
+        for i in 0..7 {
+            transport.add_response(Value::String(format!("0x{}", i)));
+        }
+        let limited = RateLimited::new(&transport, RateLimit::new(20.0, 2), RateLimit::new(1.0, 1));
+        limited.execute("cita_blockNumber", vec![]).wait().unwrap();
+        limited.execute("cita_blockNumber", vec![]).wait().unwrap();
+        let requests = (0..4)
+            .map(|_| limited.prepare("cita_blockNumber", vec![]))
+            .collect::<Vec<_>>();
+
+        // when
+        let started = Instant::now();
+        let batch = limited.send_batch(requests);
+        let single = limited.execute("cita_blockNumber", vec![]);
+        assert_eq!(limited.queue_depth(), 2);
+        let batch = batch.wait();
+        let batch_elapsed = started.elapsed();
+        let single = single.wait();
+
+        // then
+        assert_eq!(batch.map(|results| results.len()), Ok(4));
+        assert!(single.is_ok());
+        assert!(batch_elapsed >= Duration::from_millis(90));
+        assert!(started.elapsed() >= Duration::from_millis(240));
+        assert_eq!(limited.queue_depth(), 0);
+    }
+
+    #[test]
+    fn should_return_tokens_of_dropped_requests() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        let limited = RateLimited::new(&transport, RateLimit::new(1.0, 1), RateLimit::new(1.0, 1));
+
+        // when
+        drop(limited.execute("cita_blockNumber", vec![]));
+        let started = Instant::now();
+        let result = limited.execute("cita_blockNumber", vec![]).wait();
+
+        // then
+        assert_eq!(result, Ok(Value::String("0x1".into())));
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,569 @@
+//! Record and Replay Transports
+//!
+//! `Recorder` wraps any transport and writes every request together with its response
+//! to a fixture (one JSON object per line). `Replay` serves the recorded responses offline.
+//!
+//! Exchanges are written in the order the requests were sent (not the order responses arrived),
+//! so `ReplayMode::Sequential` works for sessions with concurrent requests too.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::{fmt, mem};
+use futures::{future, Async, Future, Poll};
+use parking_lot::Mutex;
+use rpc;
+use serde_json;
+use transports::Result;
+use transports::mock::method_and_params;
+use {helpers, BatchTransport, Error, ErrorKind, RequestId, Transport};
+
+/// Single recorded request and its outcome.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    /// Called method
+    pub method: String,
+    /// Call parameters
+    pub params: Vec<rpc::Value>,
+    /// Successful result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<rpc::Value>,
+    /// Error returned by the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<rpc::Error>,
+    /// Transport failure (not returned by the node)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+impl Exchange {
+    fn new(method: String, params: Vec<rpc::Value>, response: &Result<rpc::Value>) -> Self {
+        let mut exchange = Exchange {
+            method,
+            params,
+            result: None,
+            error: None,
+            failure: None,
+        };
+
+        match *response {
+            Ok(ref value) => exchange.result = Some(value.clone()),
+            Err(ref err) => match *err.kind() {
+                ErrorKind::Rpc(ref err) => exchange.error = Some(err.clone()),
+                ref other => exchange.failure = Some(format!("{}", other)),
+            },
+        }
+
+        exchange
+    }
+
+    fn response(&self) -> Result<rpc::Value> {
+        match (&self.result, &self.error, &self.failure) {
+            (&Some(ref result), _, _) => Ok(result.clone()),
+            (_, &Some(ref error), _) => Err(ErrorKind::Rpc(error.clone()).into()),
+            (_, _, &Some(ref failure)) => Err(ErrorKind::Transport(failure.clone()).into()),
+            _ => Ok(rpc::Value::Null),
+        }
+    }
+
+    fn matches(&self, method: &str, params: &[rpc::Value]) -> bool {
+        self.method == method && &*self.params == params
+    }
+}
+
+/// Writes exchanges in the order their requests were sent.
+struct Journal {
+    writer: Box<Write + Send>,
+    // Sequence number of the next request sent.
+    sent: u64,
+    // Sequence number of the next request to write.
+    written: u64,
+    completed: BTreeMap<u64, Vec<Exchange>>,
+}
+
+impl Journal {
+    fn begin(&mut self) -> u64 {
+        self.sent += 1;
+        self.sent - 1
+    }
+
+    /// Stores exchanges of given request, writing all requests which are not waiting for earlier ones.
+    /// Requests dropped before completion are finished with no exchanges.
+    fn finish(&mut self, seq: u64, exchanges: Vec<Exchange>) {
+        self.completed.insert(seq, exchanges);
+        while let Some(exchanges) = self.completed.remove(&self.written) {
+            self.written += 1;
+            for exchange in exchanges {
+                let res = writeln!(self.writer, "{}", helpers::to_string(&exchange));
+                if let Err(err) = res {
+                    warn!("Unable to record {}: {:?}", exchange.method, err);
+                }
+            }
+        }
+        if let Err(err) = self.writer.flush() {
+            warn!("Unable to flush recorded exchanges: {:?}", err);
+        }
+    }
+}
+
+type Sink = Arc<Mutex<Journal>>;
+
+/// Transport recording all requests and responses of the inner transport.
+#[derive(Clone)]
+pub struct Recorder<T> {
+    transport: T,
+    sink: Sink,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Recorder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+impl<T: Transport> Recorder<T> {
+    /// Creates new recorder writing exchanges to given writer.
+    pub fn new<W>(transport: T, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Recorder {
+            transport,
+            sink: Arc::new(Mutex::new(Journal {
+                writer: Box::new(writer),
+                sent: 0,
+                written: 0,
+                completed: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Creates new recorder appending exchanges to a fixture file.
+    pub fn to_file<P: AsRef<Path>>(transport: T, path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(transport, file))
+    }
+
+    /// Borrows the inner transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    type Out = Recording<T::Out>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        self.transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: rpc::Call) -> Self::Out {
+        Recording {
+            seq: Some(self.sink.lock().begin()),
+            call: Some(method_and_params(&request)),
+            inner: self.transport.send(id, request),
+            sink: self.sink.clone(),
+        }
+    }
+}
+
+impl<T: BatchTransport> BatchTransport for Recorder<T> {
+    type Batch = RecordingBatch<T::Batch>;
+
+    fn send_batch<I>(&self, requests: I) -> Self::Batch
+    where
+        I: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let requests = requests.into_iter().collect::<Vec<_>>();
+        let calls = requests
+            .iter()
+            .map(|&(_, ref request)| method_and_params(request))
+            .collect();
+
+        RecordingBatch {
+            seq: Some(self.sink.lock().begin()),
+            calls,
+            inner: self.transport.send_batch(requests),
+            sink: self.sink.clone(),
+        }
+    }
+}
+
+/// A future recording the response of a single call.
+pub struct Recording<F> {
+    seq: Option<u64>,
+    call: Option<(String, Vec<rpc::Value>)>,
+    inner: F,
+    sink: Sink,
+}
+
+impl<F> Future for Recording<F>
+where
+    F: Future<Item = rpc::Value, Error = Error>,
+{
+    type Item = rpc::Value;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let response = match self.inner.poll() {
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(value)) => Ok(value),
+            Err(err) => Err(err),
+        };
+
+        if let (Some(seq), Some((method, params))) = (self.seq.take(), self.call.take()) {
+            let exchange = Exchange::new(method, params, &response);
+            self.sink.lock().finish(seq, vec![exchange]);
+        }
+
+        response.map(Async::Ready)
+    }
+}
+
+impl<F> Drop for Recording<F> {
+    fn drop(&mut self) {
+        if let Some(seq) = self.seq.take() {
+            self.sink.lock().finish(seq, vec![]);
+        }
+    }
+}
+
+/// A future recording responses of all calls within a batch.
+pub struct RecordingBatch<F> {
+    seq: Option<u64>,
+    calls: Vec<(String, Vec<rpc::Value>)>,
+    inner: F,
+    sink: Sink,
+}
+
+impl<F> Future for RecordingBatch<F>
+where
+    F: Future<Item = Vec<Result<rpc::Value>>, Error = Error>,
+{
+    type Item = Vec<Result<rpc::Value>>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let response = match self.inner.poll() {
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(values)) => Ok(values),
+            Err(err) => Err(err),
+        };
+
+        let calls = mem::replace(&mut self.calls, vec![]);
+        let exchanges = calls
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (method, params))| {
+                let result = match response {
+                    Ok(ref results) => results
+                        .get(idx)
+                        .cloned()
+                        .unwrap_or_else(|| Err(ErrorKind::Internal.into())),
+                    Err(ref err) => Err(err.clone()),
+                };
+                Exchange::new(method, params, &result)
+            })
+            .collect();
+        if let Some(seq) = self.seq.take() {
+            self.sink.lock().finish(seq, exchanges);
+        }
+
+        response.map(Async::Ready)
+    }
+}
+
+impl<F> Drop for RecordingBatch<F> {
+    fn drop(&mut self) {
+        if let Some(seq) = self.seq.take() {
+            self.sink.lock().finish(seq, vec![]);
+        }
+    }
+}
+/// Order in which recorded responses are served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Requests must arrive in exactly the recorded order.
+    Sequential,
+    /// Each request is served by the first unused exchange with the same method and params.
+    Matching,
+}
+
+#[derive(Debug)]
+struct ReplayState {
+    id: RequestId,
+    exchanges: VecDeque<Exchange>,
+}
+
+/// Transport serving responses from recorded exchanges.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    mode: ReplayMode,
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl Replay {
+    /// Creates new replay transport serving given exchanges.
+    pub fn new<I>(exchanges: I, mode: ReplayMode) -> Self
+    where
+        I: IntoIterator<Item = Exchange>,
+    {
+        Replay {
+            mode,
+            state: Arc::new(Mutex::new(ReplayState {
+                id: 0,
+                exchanges: exchanges.into_iter().collect(),
+            })),
+        }
+    }
+
+    /// Loads exchanges from a fixture file produced by `Recorder`.
+    pub fn from_file<P: AsRef<Path>>(path: P, mode: ReplayMode) -> io::Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        let mut exchanges = vec![];
+        for (no, line) in file.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let exchange = serde_json::from_str(&line).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid exchange at line {}: {}", no + 1, err),
+                )
+            })?;
+            exchanges.push(exchange);
+        }
+
+        Ok(Self::new(exchanges, mode))
+    }
+
+    /// Returns number of exchanges not served yet.
+    pub fn remaining(&self) -> usize {
+        self.state.lock().exchanges.len()
+    }
+
+    /// Returns `true` if all recorded exchanges were served.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    fn respond(&self, request: &rpc::Call) -> Result<rpc::Value> {
+        let (method, params) = method_and_params(request);
+        let mut state = self.state.lock();
+
+        let position = match self.mode {
+            ReplayMode::Sequential => match state.exchanges.front() {
+                Some(next) if next.matches(&method, &params) => Some(0),
+                Some(next) => {
+                    return Err(ErrorKind::Transport(format!(
+                        "Replay mismatch: expected {} {}, got {} {}",
+                        next.method,
+                        helpers::to_string(&next.params),
+                        method,
+                        helpers::to_string(&params)
+                    )).into())
+                }
+                None => None,
+            },
+            ReplayMode::Matching => state
+                .exchanges
+                .iter()
+                .position(|exchange| exchange.matches(&method, &params)),
+        };
+
+        match position.and_then(|position| state.exchanges.remove(position)) {
+            Some(exchange) => exchange.response(),
+            None => Err(ErrorKind::Transport(format!(
+                "No recorded response for {} {}",
+                method,
+                helpers::to_string(&params)
+            )).into()),
+        }
+    }
+}
+
+impl Transport for Replay {
+    type Out = ::Result<rpc::Value>;
+
+    fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+        let id = {
+            let mut state = self.state.lock();
+            state.id += 1;
+            state.id
+        };
+        (id, helpers::build_request(id, method, params))
+    }
+
+    fn send(&self, _id: RequestId, request: rpc::Call) -> Self::Out {
+        Box::new(future::result(self.respond(&request)))
+    }
+}
+
+impl BatchTransport for Replay {
+    type Batch = ::Result<Vec<Result<rpc::Value>>>;
+
+    fn send_batch<T>(&self, requests: T) -> Self::Batch
+    where
+        T: IntoIterator<Item = (RequestId, rpc::Call)>,
+    {
+        let results = requests
+            .into_iter()
+            .map(|(_, request)| self.respond(&request))
+            .collect();
+        Box::new(future::ok(results))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io, process};
+    use std::sync::Arc;
+    use futures::Future;
+    use parking_lot::Mutex;
+    use rpc::{self, Value};
+    use api::{Cita, Namespace};
+    use transports::MockTransport;
+    use serde_json;
+    use types::U256;
+    use {ErrorKind, Transport};
+    use super::{Exchange, Recorder, Replay, ReplayMode};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn methods(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Exchange>(line).unwrap().method)
+                .collect()
+        }
+    }
+
+    fn exchange(method: &str, params: Vec<Value>, result: &str) -> Exchange {
+        Exchange {
+            method: method.into(),
+            params,
+            result: Some(Value::String(result.into())),
+            error: None,
+            failure: None,
+        }
+    }
+
+    #[test]
+    fn should_record_and_replay_a_session() {
+        // given
+        let path = env::temp_dir().join(format!("web3-replay-{}.jsonl", process::id()));
+        let _ = fs::remove_file(&path);
+        let mock = MockTransport::new();
+        mock.respond("cita_blockNumber", "0x10")
+            .fail("cita_getTransaction", rpc::Error::invalid_params("hash"));
+
+        {
+            let recorder = Recorder::to_file(mock, &path).unwrap();
+            let cita = Cita::new(&recorder);
+            cita.block_number().wait().unwrap();
+            cita.transaction(1.into()).wait().unwrap_err();
+        }
+
+        // when
+        let replay = Replay::from_file(&path, ReplayMode::Sequential).unwrap();
+        let cita = Cita::new(&replay);
+        let height = cita.block_number().wait();
+        let tx = cita.transaction(1.into()).wait();
+        fs::remove_file(&path).unwrap();
+
+        // then
+        assert_eq!(height, Ok(U256::from(0x10)));
+        assert_eq!(
+            tx.unwrap_err(),
+            ErrorKind::Rpc(rpc::Error::invalid_params("hash")).into()
+        );
+        assert!(replay.is_exhausted());
+    }
+
+    #[test]
+    fn should_match_by_method_and_params() {
+        // given
+        let replay = Replay::new(
+            vec![
+                exchange("eth_getCode", vec![Value::String("a".into())], "0x01"),
+                exchange("eth_getCode", vec![Value::String("b".into())], "0x02"),
+            ],
+            ReplayMode::Matching,
+        );
+
+        // when
+        let b = replay.execute("eth_getCode", vec![Value::String("b".into())]).wait();
+        let a = replay.execute("eth_getCode", vec![Value::String("a".into())]).wait();
+        let unmatched = replay.execute("eth_getCode", vec![Value::String("a".into())]).wait();
+
+        // then
+        assert_eq!(b, Ok(Value::String("0x02".into())));
+        assert_eq!(a, Ok(Value::String("0x01".into())));
+        assert_eq!(
+            unmatched,
+            Err(ErrorKind::Transport(r#"No recorded response for eth_getCode ["a"]"#.into()).into())
+        );
+    }
+
+    #[test]
+    fn should_report_out_of_order_requests() {
+        // given
+        let replay = Replay::new(
+            vec![exchange("cita_blockNumber", vec![], "0x1")],
+            ReplayMode::Sequential,
+        );
+
+        // when
+        let res = replay.execute("net_peerCount", vec![]).wait();
+
+        // then
+        assert_eq!(
+            res,
+            Err(ErrorKind::Transport("Replay mismatch: expected cita_blockNumber [], got net_peerCount []".into()).into())
+        );
+        assert_eq!(replay.remaining(), 1);
+    }
+
+    #[test]
+    fn should_record_in_order_of_requests() {
+        // given
+        let buffer = Buffer::default();
+        let mock = MockTransport::new();
+        mock.respond("cita_blockNumber", "0x10")
+            .respond("net_peerCount", "0x2")
+            .respond("cita_getBlockByHash", Value::Null);
+        let recorder = Recorder::new(mock, buffer.clone());
+
+        // when
+        let first = recorder.execute("cita_blockNumber", vec![]);
+        let dropped = recorder.execute("cita_getBlockByHash", vec![]);
+        let second = recorder.execute("net_peerCount", vec![]);
+        second.wait().unwrap();
+        let written_early = buffer.methods();
+        first.wait().unwrap();
+        drop(dropped);
+
+        // then
+        assert!(written_early.is_empty());
+        assert_eq!(buffer.methods(), vec!["cita_blockNumber", "net_peerCount"]);
+    }
+}
@@ -1,8 +1,11 @@
 use std::{fmt, mem, thread};
 use std::sync::{self, atomic, Arc};
+use std::time::Duration;
 use futures::{self, Future};
 use futures::sync::oneshot;
+use tokio_timer::{Sleep, Timer};
 use transports::Result;
+use transports::timer::{cap_timeout, request_timer};
 use transports::tokio_core::reactor;
 use {Error, ErrorKind, RequestId};
 
@@ -81,6 +84,51 @@ impl Drop for EventLoopHandle {
     }
 }
 
+/// Default timeout of requests sent by a transport.
+/// The process-wide request timer is used only if a timeout is set.
+#[derive(Clone, Default)]
+pub struct RequestTimeout {
+    timer: Option<Timer>,
+    duration: Option<Duration>,
+}
+
+impl fmt::Debug for RequestTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RequestTimeout")
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+impl RequestTimeout {
+    /// Returns a copy of this configuration with different timeout (`None` disables it).
+    /// Timeouts longer than `MAX_REQUEST_TIMEOUT_SECS` are capped to the maximum.
+    pub fn with_duration(&self, duration: Option<Duration>) -> Self {
+        let duration = duration.map(cap_timeout);
+
+        let timer = match (self.timer.as_ref(), duration) {
+            (Some(timer), _) => Some(timer.clone()),
+            (None, Some(_)) => Some(request_timer()),
+            (None, None) => None,
+        };
+
+        RequestTimeout { timer, duration }
+    }
+
+    /// Returns configured timeout.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Starts a timeout for a new request.
+    pub fn start(&self) -> Option<Sleep> {
+        match (self.timer.as_ref(), self.duration) {
+            (Some(timer), Some(duration)) => Some(timer.sleep(duration)),
+            _ => None,
+        }
+    }
+}
+
 type PendingResult<O> = oneshot::Receiver<Result<O>>;
 
 enum RequestState<O> {
@@ -90,10 +138,14 @@ enum RequestState<O> {
 }
 
 /// A future representing a response to a pending request.
+/// Resolves to `ErrorKind::Timeout` if the response doesn't arrive in time.
+/// Dropping the future (or timing out) drops the receiver, which lets the transport cancel the request.
 pub struct Response<T, O> {
     id: RequestId,
     state: RequestState<O>,
     extract: T,
+    timeout: Option<Sleep>,
+    cleanup: Option<Box<Fn() + Send>>,
 }
 
 impl<T, O> Response<T, O> {
@@ -103,8 +155,58 @@ impl<T, O> Response<T, O> {
             id,
             extract,
             state: RequestState::Sending(Some(result), rx),
+            timeout: None,
+            cleanup: None,
+        }
+    }
+
+    /// Fails the request if it doesn't resolve before `timeout` fires.
+    pub fn with_timeout(mut self, timeout: Option<Sleep>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets a function invoked if the request is dropped or times out before getting a response.
+    pub fn on_cancel<F>(mut self, cleanup: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.cleanup = Some(Box::new(cleanup));
+        self
+    }
+
+    fn cancel(&mut self) {
+        if let Some(cleanup) = self.cleanup.take() {
+            trace!("[{}] Cleaning up cancelled request.", self.id);
+            cleanup();
         }
     }
+
+    fn poll_timeout(&mut self) -> Result<()> {
+        let expired = match self.timeout {
+            Some(ref mut timeout) => match timeout.poll() {
+                Ok(futures::Async::Ready(())) => true,
+                Ok(futures::Async::NotReady) => false,
+                Err(err) => {
+                    return Err(ErrorKind::Transport(format!("Invalid request timeout: {:?}", err)).into());
+                }
+            },
+            None => false,
+        };
+
+        if expired {
+            debug!("[{}] Request timed out.", self.id);
+            Err(ErrorKind::Timeout.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T, O> Drop for Response<T, O> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
 }
 
 impl<T, O, Out> Future for Response<T, O>
@@ -117,27 +219,44 @@ where
 
     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
         loop {
-            let extract = &self.extract;
-            match self.state {
+            let response = match self.state {
                 RequestState::Sending(ref mut result, _) => {
                     trace!("[{}] Request pending.", self.id);
                     if let Some(Err(e)) = result.take() {
                         return Err(e);
                     }
+                    None
                 }
                 RequestState::WaitingForResponse(ref mut rx) => {
                     trace!("[{}] Checking response.", self.id);
-                    let result = try_ready!(
-                        rx.poll()
-                            .map_err(|_| Error::from(ErrorKind::Io(::std::io::ErrorKind::TimedOut.into())))
-                    );
-                    trace!("[{}] Extracting result.", self.id);
-                    return result.and_then(|x| extract(x)).map(futures::Async::Ready);
+                    match rx.poll() {
+                        Ok(futures::Async::Ready(result)) => Some(result),
+                        Ok(futures::Async::NotReady) => None,
+                        Err(_) => Some(Err(ErrorKind::Io(::std::io::ErrorKind::TimedOut.into()).into())),
+                    }
                 }
                 RequestState::Done => {
                     return Err(ErrorKind::Unreachable.into());
                 }
+            };
+
+            if let Some(result) = response {
+                // The response has been delivered, there is nothing left to clean up.
+                self.cleanup = None;
+                trace!("[{}] Extracting result.", self.id);
+                let extract = &self.extract;
+                return result.and_then(|x| extract(x)).map(futures::Async::Ready);
+            }
+
+            if let RequestState::WaitingForResponse(_) = self.state {
+                if let Err(err) = self.poll_timeout() {
+                    self.state = RequestState::Done;
+                    self.cancel();
+                    return Err(err);
+                }
+                return Ok(futures::Async::NotReady);
             }
+
             // Proceeed to the next state
             let state = mem::replace(&mut self.state, RequestState::Done);
             self.state = if let RequestState::Sending(_, rx) = state {
@@ -148,3 +267,31 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use futures::{self, Async, Future};
+    use transports::MAX_REQUEST_TIMEOUT_SECS;
+    use super::RequestTimeout;
+
+    #[test]
+    fn should_start_timeouts_up_to_the_maximum() {
+        let max = Duration::from_secs(MAX_REQUEST_TIMEOUT_SECS);
+        let timeout = RequestTimeout::default().with_duration(Some(max));
+
+        let mut sleep = timeout.start().unwrap();
+
+        assert_eq!(timeout.duration(), Some(max));
+        // would fail with `TooLong` if the wheel didn't cover the maximal timeout
+        assert_eq!(futures::lazy(|| sleep.poll()).wait().ok(), Some(Async::NotReady));
+        assert!(RequestTimeout::default().with_duration(None).start().is_none());
+    }
+
+    #[test]
+    fn should_cap_timeouts_over_the_maximum() {
+        let timeout = RequestTimeout::default().with_duration(Some(Duration::from_secs(24 * 60 * 60)));
+
+        assert_eq!(timeout.duration(), Some(Duration::from_secs(MAX_REQUEST_TIMEOUT_SECS)));
+    }
+}
@@ -0,0 +1,42 @@
+//! Timer tracking request timeouts of all transports and blocking clients.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use parking_lot::Mutex;
+use tokio_timer::{self, Timer};
+
+/// Longest supported request timeout (20 minutes).
+pub const MAX_REQUEST_TIMEOUT_SECS: u64 = 20 * 60;
+
+// 100ms ticks, so that the wheel covers the maximal timeout (`Timer::default` only covers ~409s).
+const TIMER_TICK_MS: u64 = 100;
+const TIMER_SLOTS: usize = 16_384;
+
+/// Returns the timer covering timeouts up to `MAX_REQUEST_TIMEOUT_SECS`.
+/// Its thread is started on first use and shared by the whole process.
+pub(crate) fn request_timer() -> Timer {
+    static TIMER: OnceLock<Mutex<Timer>> = OnceLock::new();
+    TIMER
+        .get_or_init(|| {
+            Mutex::new(
+                tokio_timer::wheel()
+                    .tick_duration(Duration::from_millis(TIMER_TICK_MS))
+                    .num_slots(TIMER_SLOTS)
+                    .max_timeout(Duration::from_secs(MAX_REQUEST_TIMEOUT_SECS))
+                    .build(),
+            )
+        })
+        .lock()
+        .clone()
+}
+
+/// Caps the timeout to `MAX_REQUEST_TIMEOUT_SECS`.
+pub(crate) fn cap_timeout(timeout: Duration) -> Duration {
+    let max = Duration::from_secs(MAX_REQUEST_TIMEOUT_SECS);
+    if timeout > max {
+        warn!("Request timeout {:?} exceeds the maximum, using {:?}.", timeout, max);
+        max
+    } else {
+        timeout
+    }
+}
@@ -1,9 +1,9 @@
 use serde::{Serialize, Serializer};
-use types::{Bytes, H160, H2048, H256, U256, U64};
+use types::{Bloom, Bytes, H160, H256, U256, U64};
 
 /// The block type returned from RPC calls.
 /// This is generic over a `TX` type.
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Block<TX> {
     /// Hash of the block
     pub hash: Option<H256>,
@@ -38,7 +38,7 @@ pub struct Block<TX> {
     pub extra_data: Bytes,
     /// Logs bloom
     #[serde(rename = "logsBloom")]
-    pub logs_bloom: H2048,
+    pub logs_bloom: Bloom,
     /// Timestamp
     pub timestamp: U256,
     /// Difficulty
@@ -0,0 +1,188 @@
+use std::ops;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tiny_keccak::keccak256;
+use types::{Address, Filter, Log, H2048, H256};
+
+const BLOOM_BYTES: usize = 256;
+
+/// 2048-bit logs bloom of a block or receipt.
+///
+/// Every address and topic of a log sets three bits of the bloom,
+/// so a missing bit proves the entry is not there while all bits set only means it might be.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bloom(pub H2048);
+
+impl Bloom {
+    /// Returns empty bloom.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn bits(input: &[u8]) -> [(usize, u8); 3] {
+        let hash = keccak256(input);
+        let mut bits = [(0, 0); 3];
+        for (i, bit) in bits.iter_mut().enumerate() {
+            let index = ((hash[2 * i] as usize) << 8 | hash[2 * i + 1] as usize) & (BLOOM_BYTES * 8 - 1);
+            *bit = (BLOOM_BYTES - 1 - index / 8, 1 << (index % 8));
+        }
+        bits
+    }
+
+    /// Adds raw entry (address or topic bytes) to the bloom.
+    pub fn accrue(&mut self, input: &[u8]) {
+        for &(byte, mask) in Self::bits(input).iter() {
+            (self.0).0[byte] |= mask;
+        }
+    }
+
+    /// Adds address and all topics of the log to the bloom.
+    pub fn accrue_log(&mut self, log: &Log) {
+        self.accrue(&log.address);
+        for topic in &log.topics {
+            self.accrue(topic);
+        }
+    }
+
+    /// Adds all entries of other bloom.
+    pub fn accrue_bloom(&mut self, other: &Bloom) {
+        for (byte, other) in (self.0).0.iter_mut().zip((other.0).0.iter()) {
+            *byte |= *other;
+        }
+    }
+
+    /// Returns `false` if raw entry is certainly not in the bloom.
+    pub fn contains_input(&self, input: &[u8]) -> bool {
+        Self::bits(input)
+            .iter()
+            .all(|&(byte, mask)| (self.0).0[byte] & mask == mask)
+    }
+
+    /// Returns `false` if the address certainly didn't log anything.
+    pub fn contains_address(&self, address: &Address) -> bool {
+        self.contains_input(address)
+    }
+
+    /// Returns `false` if the topic is certainly not in any log.
+    pub fn contains_topic(&self, topic: &H256) -> bool {
+        self.contains_input(topic)
+    }
+
+    /// Returns `false` if some entry of other bloom is certainly not in this one.
+    pub fn contains_bloom(&self, other: &Bloom) -> bool {
+        (self.0)
+            .0
+            .iter()
+            .zip((other.0).0.iter())
+            .all(|(byte, other)| byte & other == *other)
+    }
+
+    /// Returns `false` if no log can match the filter's addresses and topics.
+    pub fn matches(&self, filter: &Filter) -> bool {
+        filter.matches_bloom(self)
+    }
+}
+
+impl From<H2048> for Bloom {
+    fn from(bloom: H2048) -> Self {
+        Bloom(bloom)
+    }
+}
+
+impl From<Bloom> for H2048 {
+    fn from(bloom: Bloom) -> Self {
+        bloom.0
+    }
+}
+
+impl<'a> From<&'a Log> for Bloom {
+    fn from(log: &'a Log) -> Self {
+        let mut bloom = Bloom::new();
+        bloom.accrue_log(log);
+        bloom
+    }
+}
+
+impl ops::Deref for Bloom {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Serialize for Bloom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'a> Deserialize<'a> for Bloom {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        H2048::deserialize(deserializer).map(Bloom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use types::{Address, Bloom, Bytes, FilterBuilder, Log, H256};
+
+    fn log() -> Log {
+        Log {
+            address: Address::from(0x10),
+            topics: vec![H256::from(1), H256::from(2)],
+            data: Bytes::default(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+        }
+    }
+
+    #[test]
+    fn should_accrue_and_check_entries() {
+        // given
+        let mut bloom = Bloom::new();
+        assert!(!bloom.contains_address(&Address::from(0x10)));
+
+        // when
+        bloom.accrue_log(&log());
+
+        // then
+        let bits = bloom.iter().map(|byte| byte.count_ones()).sum::<u32>();
+        assert!(bits > 0 && bits <= 9);
+        assert!(bloom.contains_address(&Address::from(0x10)));
+        assert!(bloom.contains_topic(&H256::from(1)));
+        assert!(bloom.contains_topic(&H256::from(2)));
+        assert!(!bloom.contains_topic(&H256::from(3)));
+        assert!(bloom.contains_bloom(&Bloom::from(&log())));
+    }
+
+    #[test]
+    fn should_match_filters() {
+        let bloom = Bloom::from(&log());
+        let filter = |address: u64, topic: u64| {
+            FilterBuilder::default()
+                .address(vec![Address::from(0x20), Address::from(address)])
+                .topics(None, Some(vec![H256::from(topic)]), None, None)
+                .build()
+        };
+
+        assert!(bloom.matches(&FilterBuilder::default().build()));
+        assert!(bloom.matches(&filter(0x10, 2)));
+        assert!(!bloom.matches(&filter(0x11, 2)));
+        assert!(!bloom.matches(&filter(0x10, 3)));
+    }
+
+    #[test]
+    fn should_serialize_like_h2048() {
+        let bloom = Bloom::from(&log());
+
+        let json = serde_json::to_string(&bloom).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&bloom.0).unwrap());
+        assert_eq!(serde_json::from_str::<Bloom>(&json).unwrap(), bloom);
+    }
+}
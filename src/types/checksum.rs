@@ -0,0 +1,154 @@
+//! EIP-55 mixed-case checksum addresses.
+
+use std::fmt;
+use tiny_keccak::keccak256;
+use util;
+use types::{FromStrError, H160};
+
+const ADDRESS_LEN: usize = 20;
+
+/// Displays address bytes in EIP-55 checksummed form, e.g. `0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed`.
+#[derive(Clone, Copy)]
+pub struct Checksummed<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for Checksummed<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&checksum(self.0))
+    }
+}
+
+impl<'a> fmt::Debug for Checksummed<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+fn checksum(address: &[u8]) -> String {
+    let lower = address.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let hash = keccak256(lower.as_bytes());
+
+    let mut result = String::with_capacity(lower.len() + 2);
+    result.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if nibble >= 8 {
+            result.extend(c.to_uppercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Parses `0x`-prefixed 40 character address.
+///
+/// All-lowercase and all-uppercase addresses carry no checksum and are accepted,
+/// mixed-case addresses have to match their EIP-55 checksum.
+fn parse(s: &str) -> Result<[u8; ADDRESS_LEN], FromStrError> {
+    let expected = ADDRESS_LEN * 2 + 2;
+    if s.len() != expected {
+        return Err(FromStrError::InvalidLength {
+            got: s.len(),
+            expected,
+        });
+    }
+
+    let address = H160::from_hex_str(s)?;
+    let digits = &s[2..];
+    let has_lower = digits.chars().any(|c| c.is_lowercase());
+    let has_upper = digits.chars().any(|c| c.is_uppercase());
+    if has_lower && has_upper && checksum(&address.0)[2..] != *digits {
+        return Err(FromStrError::InvalidChecksum);
+    }
+
+    Ok(address.0)
+}
+
+/// EIP-55 checksummed representation of addresses.
+pub trait Checksum: Sized {
+    /// Returns displayable checksummed representation of the address.
+    fn checksummed(&self) -> Checksummed;
+
+    /// Strictly parses full-length address, rejecting mixed-case input with invalid checksum.
+    fn from_checksummed(s: &str) -> Result<Self, FromStrError>;
+}
+
+impl Checksum for H160 {
+    fn checksummed(&self) -> Checksummed {
+        Checksummed(&self.0)
+    }
+
+    fn from_checksummed(s: &str) -> Result<Self, FromStrError> {
+        parse(s).map(H160)
+    }
+}
+
+impl Checksum for util::Address {
+    fn checksummed(&self) -> Checksummed {
+        Checksummed(&self[..])
+    }
+
+    fn from_checksummed(s: &str) -> Result<Self, FromStrError> {
+        parse(s).map(|address| util::Address::from_slice(&address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use util;
+    use types::{FromStrError, H160};
+    use super::Checksum;
+
+    const VALID: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn should_display_checksummed_address() {
+        for address in VALID.iter() {
+            let parsed = H160::from_hex_str(&address.to_lowercase()).unwrap();
+
+            assert_eq!(parsed.checksummed().to_string(), *address);
+        }
+    }
+
+    #[test]
+    fn should_parse_checksummed_address() {
+        for address in VALID.iter() {
+            let parsed = H160::from_checksummed(address).unwrap();
+
+            assert_eq!(parsed, H160::from_hex_str(address).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_accept_single_case_addresses() {
+        let lower = H160::from_checksummed("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+        let upper = H160::from_checksummed("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED").unwrap();
+
+        assert_eq!(lower, upper);
+    }
+
+    #[test]
+    fn should_reject_invalid_checksum() {
+        match H160::from_checksummed("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD") {
+            Err(FromStrError::InvalidChecksum) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match H160::from_checksummed("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA") {
+            Err(FromStrError::InvalidLength { got: 40, expected: 42 }) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_checksum_cita_address() {
+        let address = util::Address::from_checksummed(VALID[1]).unwrap();
+
+        assert_eq!(address.checksummed().to_string(), VALID[1]);
+        assert!(util::Address::from_checksummed(&VALID[1].replace("fB", "Fb")).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use types::{BlockNumber, Bytes, H160, H256, U256};
+use ethabi::{Event, Token};
+use tiny_keccak::keccak256;
+use contract::tokens::Tokenizable;
+use types::{Bloom, BlockNumber, Bytes, H160, H256, U256};
 
 /// A log produced by a transaction.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -46,10 +49,76 @@ pub struct Filter {
     limit: Option<usize>,
 }
 
+impl Filter {
+    /// Returns `false` if no log in a block with given bloom can match the filter.
+    pub fn matches_bloom(&self, bloom: &Bloom) -> bool {
+        fn any_of<T, F: Fn(&T) -> bool>(entries: &Option<Vec<T>>, contains: F) -> bool {
+            match *entries {
+                Some(ref entries) if !entries.is_empty() => entries.iter().any(contains),
+                _ => true,
+            }
+        }
+
+        let topics = self.topics.as_ref().map_or(&[][..], |topics| &topics[..]);
+        any_of(&self.address, |address| bloom.contains_address(address))
+            && topics
+                .iter()
+                .all(|topic| any_of(topic, |topic| bloom.contains_topic(topic)))
+    }
+}
+
+/// Appends in-place ABI encoding of the token, as hashed for indexed arrays.
+fn encode_in_place(token: &Token, out: &mut Vec<u8>) {
+    fn pad_right(bytes: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(bytes);
+        let len = (32 - bytes.len() % 32) % 32;
+        out.extend(::std::iter::repeat(0).take(len));
+    }
+
+    match *token {
+        Token::Address(ref address) => {
+            out.extend_from_slice(&[0; 12]);
+            out.extend_from_slice(address);
+        }
+        Token::Int(ref value) | Token::Uint(ref value) => out.extend_from_slice(value),
+        Token::Bool(value) => {
+            out.extend_from_slice(&[0; 31]);
+            out.push(value as u8);
+        }
+        Token::FixedBytes(ref bytes) | Token::Bytes(ref bytes) => pad_right(bytes, out),
+        Token::String(ref string) => pad_right(string.as_bytes(), out),
+        Token::FixedArray(ref tokens) | Token::Array(ref tokens) => for token in tokens {
+            encode_in_place(token, out);
+        },
+    }
+}
+
+/// Encodes value of an indexed event parameter as a topic.
+///
+/// Values of dynamic types (`string`, `bytes` and arrays) are hashed.
+fn encode_topic(token: &Token) -> H256 {
+    match *token {
+        Token::Bytes(ref bytes) => H256(keccak256(bytes)),
+        Token::String(ref string) => H256(keccak256(string.as_bytes())),
+        Token::FixedArray(_) | Token::Array(_) => {
+            let mut out = Vec::new();
+            encode_in_place(token, &mut out);
+            H256(keccak256(&out))
+        }
+        _ => {
+            let mut out = Vec::with_capacity(32);
+            encode_in_place(token, &mut out);
+            H256::from(&out[..])
+        }
+    }
+}
+
 /// Filter Builder
 #[derive(Default, Clone)]
 pub struct FilterBuilder {
     filter: Filter,
+    // Position of the first indexed parameter and number of indexed parameters of the event.
+    indexed: Option<(usize, usize)>,
 }
 
 impl FilterBuilder {
@@ -83,6 +152,45 @@ impl FilterBuilder {
         self
     }
 
+    /// Filters logs of given event.
+    ///
+    /// Sets the first topic to the event signature, unless the event is anonymous.
+    /// Positions passed to `indexed` are counted from the topic after the signature.
+    pub fn event(mut self, event: &Event) -> Self {
+        let offset = if event.anonymous {
+            0
+        } else {
+            self.set_topic(0, vec![H256::from(&event.signature()[..])]);
+            1
+        };
+        let count = event.inputs.iter().filter(|input| input.indexed).count();
+        self.indexed = Some((offset, count));
+        self
+    }
+
+    /// Filters logs with `index`-th indexed parameter equal to any of `values`.
+    ///
+    /// # Panics
+    ///
+    /// If `event` was not called before or the event has no `index`-th indexed parameter.
+    pub fn indexed<V: Tokenizable>(mut self, index: usize, values: Vec<V>) -> Self {
+        let (offset, count) = self.indexed.expect("`event` has to be called before `indexed`");
+        assert!(index < count, "Event has {} indexed parameters, got index {}", count, index);
+        let position = offset + index;
+        assert!(position < 4, "Logs have at most 4 topics, got position {}", position);
+        let topics = values
+            .into_iter()
+            .map(|value| encode_topic(&value.into_token()))
+            .collect();
+        self.set_topic(position, topics);
+        self
+    }
+
+    fn set_topic(&mut self, position: usize, values: Vec<H256>) {
+        let topics = self.filter.topics.get_or_insert_with(|| vec![None; 4]);
+        topics[position] = Some(values);
+    }
+
     /// Limit the result
     pub fn limit(mut self, limit: usize) -> Self {
         self.filter.limit = Some(limit);
@@ -94,3 +202,101 @@ impl FilterBuilder {
         self.filter.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethabi::{Event, EventParam, ParamType, Token};
+    use serde_json;
+    use types::{Address, H256};
+    use super::{encode_topic, FilterBuilder};
+
+    fn transfer(anonymous: bool) -> Event {
+        let param = |name: &str, kind, indexed| EventParam {
+            name: name.into(),
+            kind,
+            indexed,
+        };
+        Event {
+            name: "Transfer".into(),
+            inputs: vec![
+                param("from", ParamType::Address, true),
+                param("to", ParamType::Address, true),
+                param("value", ParamType::Uint(256), false),
+            ],
+            anonymous,
+        }
+    }
+
+    #[test]
+    fn should_build_event_filter() {
+        // when
+        let filter = FilterBuilder::default()
+            .event(&transfer(false))
+            .indexed(1, vec![Address::from(0x10), Address::from(0x20)])
+            .build();
+
+        // then
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            json!({
+                "fromBlock": null,
+                "toBlock": null,
+                "address": null,
+                "topics": [
+                    ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+                    null,
+                    [
+                        "0x0000000000000000000000000000000000000000000000000000000000000010",
+                        "0x0000000000000000000000000000000000000000000000000000000000000020"
+                    ],
+                    null
+                ],
+                "limit": null
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_set_signature_of_anonymous_event() {
+        let filter = FilterBuilder::default()
+            .event(&transfer(true))
+            .indexed(0, vec![Address::from(0x10)])
+            .build();
+
+        assert_eq!(
+            filter.topics,
+            Some(vec![Some(vec![H256::from(0x10)]), None, None, None])
+        );
+    }
+
+    #[test]
+    fn should_hash_dynamic_values() {
+        assert_eq!(
+            encode_topic(&Token::String("hello".into())),
+            H256::from_hex_str("0x1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8").unwrap()
+        );
+        assert_eq!(
+            encode_topic(&Token::Bytes(b"hello".to_vec())),
+            encode_topic(&Token::String("hello".into()))
+        );
+        assert_eq!(encode_topic(&Token::Bool(true)), H256::from(1));
+        assert_eq!(
+            encode_topic(&Token::Array(vec![Token::Bool(true), Token::Bool(false)])),
+            H256(::tiny_keccak::keccak256(&[&H256::from(1)[..], &H256::from(0)[..]].concat()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Event has 2 indexed parameters")]
+    fn should_reject_parameters_which_are_not_indexed() {
+        FilterBuilder::default()
+            .event(&transfer(false))
+            .indexed(2, vec![Address::from(1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`event` has to be called before `indexed`")]
+    fn should_require_event_before_indexed_parameters() {
+        FilterBuilder::default().indexed(0, vec![Address::from(1)]);
+    }
+}
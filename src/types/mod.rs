@@ -1,24 +1,248 @@
 //! Web3 Types
 
 mod block;
+mod bloom;
 mod bytes;
+mod checksum;
 mod log;
+mod rlp;
+#[cfg(feature = "secp256k1")]
+mod signed_transaction;
+mod sync_state;
 mod transaction;
 mod transaction_id;
 mod transaction_request;
 mod uint;
+mod units;
 mod work;
 
 pub use self::block::{Block, BlockId, BlockNumber};
+pub use self::bloom::Bloom;
 pub use self::bytes::Bytes;
+pub use self::checksum::{Checksum, Checksummed};
 pub use self::log::{Filter, FilterBuilder, Log};
+pub use self::rlp::DecoderError as RlpError;
+#[cfg(feature = "secp256k1")]
+pub use self::signed_transaction::{RawTransaction, SignedTransaction, SigningError};
+pub use self::sync_state::SyncState;
 pub use self::transaction::{Receipt as TransactionReceipt, Transaction};
 pub use self::transaction_id::TransactionId;
 pub use self::transaction_request::{CallRequest, TransactionCondition, TransactionRequest};
+pub use self::uint::decimal;
 pub use self::uint::{FromStrError, H128, H160, H2048, H256, H512, H520, H64, U256, U64};
+pub use self::units::{format_units, format_units_rounded, parse_units, Amount, Unit, UnitsError, MAX_DECIMALS};
 pub use self::work::Work;
 
 /// Address
 pub type Address = H160;
 /// Index in block
 pub type Index = U64;
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use serde_json;
+    use super::*;
+
+    /// Xorshift generator, deterministic so failures are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+
+        /// Mostly small values to exercise leading zeros in hex quantities.
+        fn uint(&mut self) -> U256 {
+            match self.next() % 4 {
+                0 => U256::zero(),
+                1 => U256::from(self.next() % 1000),
+                _ => U256::from(&self.bytes(32)[..]),
+            }
+        }
+
+        fn maybe<T, F: FnMut(&mut Rng) -> T>(&mut self, mut f: F) -> Option<T> {
+            if self.next() % 3 == 0 {
+                None
+            } else {
+                Some(f(self))
+            }
+        }
+    }
+
+    trait Random {
+        fn random(rng: &mut Rng) -> Self;
+    }
+
+    macro_rules! random_hash {
+      ($name: ident, $len: expr) => {
+        impl Random for $name {
+          fn random(rng: &mut Rng) -> Self {
+            $name::from(&rng.bytes($len)[..])
+          }
+        }
+      };
+    }
+
+    random_hash!(H160, 20);
+    random_hash!(H256, 32);
+    random_hash!(H2048, 256);
+
+    impl Random for Log {
+        fn random(rng: &mut Rng) -> Self {
+            let topics = (rng.next() % 5) as usize;
+            let data = (rng.next() % 80) as usize;
+            Log {
+                address: Random::random(rng),
+                topics: (0..topics).map(|_| Random::random(rng)).collect(),
+                data: rng.bytes(data).into(),
+                block_hash: rng.maybe(Random::random),
+                block_number: rng.maybe(Rng::uint),
+                transaction_hash: rng.maybe(Random::random),
+                transaction_index: rng.maybe(Rng::uint),
+                log_index: rng.maybe(Rng::uint),
+                transaction_log_index: rng.maybe(Rng::uint),
+            }
+        }
+    }
+
+    impl Random for Transaction {
+        fn random(rng: &mut Rng) -> Self {
+            let input = (rng.next() % 80) as usize;
+            Transaction {
+                hash: Random::random(rng),
+                nonce: rng.uint(),
+                block_hash: rng.maybe(Random::random),
+                block_number: rng.maybe(Rng::uint),
+                transaction_index: rng.maybe(|rng| rng.next().into()),
+                from: Random::random(rng),
+                to: rng.maybe(Random::random),
+                value: rng.uint(),
+                gas_price: rng.uint(),
+                gas: rng.uint(),
+                input: rng.bytes(input).into(),
+            }
+        }
+    }
+
+    impl Random for TransactionReceipt {
+        fn random(rng: &mut Rng) -> Self {
+            let logs = (rng.next() % 3) as usize;
+            TransactionReceipt {
+                transaction_hash: Random::random(rng),
+                transaction_index: rng.next().into(),
+                block_hash: Random::random(rng),
+                block_number: rng.uint(),
+                cumulative_gas_used: rng.uint(),
+                gas_used: rng.uint(),
+                contract_address: rng.maybe(Random::random),
+                logs: (0..logs).map(|_| Random::random(rng)).collect(),
+                logs_bloom: Random::random(rng),
+                status: rng.maybe(|rng| (rng.next() % 2).into()),
+                root: rng.maybe(Random::random),
+            }
+        }
+    }
+
+    impl<TX: Random> Random for Block<TX> {
+        fn random(rng: &mut Rng) -> Self {
+            let extra_data = (rng.next() % 32) as usize;
+            let seal_fields = (rng.next() % 3) as usize;
+            let uncles = (rng.next() % 3) as usize;
+            let transactions = (rng.next() % 4) as usize;
+            Block {
+                hash: rng.maybe(Random::random),
+                parent_hash: Random::random(rng),
+                uncles_hash: Random::random(rng),
+                author: Random::random(rng),
+                state_root: Random::random(rng),
+                transactions_root: Random::random(rng),
+                receipts_root: Random::random(rng),
+                number: rng.maybe(|rng| rng.next().into()),
+                gas_used: rng.uint(),
+                gas_limit: rng.uint(),
+                extra_data: rng.bytes(extra_data).into(),
+                logs_bloom: Random::random(rng),
+                timestamp: rng.uint(),
+                difficulty: rng.uint(),
+                total_difficulty: rng.uint(),
+                seal_fields: (0..seal_fields).map(|_| rng.bytes(8).into()).collect(),
+                uncles: (0..uncles).map(|_| Random::random(rng)).collect(),
+                transactions: (0..transactions).map(|_| Random::random(rng)).collect(),
+                size: rng.maybe(Rng::uint),
+            }
+        }
+    }
+
+    impl Random for Work {
+        fn random(rng: &mut Rng) -> Self {
+            Work {
+                pow_hash: Random::random(rng),
+                seed_hash: Random::random(rng),
+                target: Random::random(rng),
+                number: rng.maybe(Rng::next),
+            }
+        }
+    }
+
+    impl Random for Bloom {
+        fn random(rng: &mut Rng) -> Self {
+            Bloom(Random::random(rng))
+        }
+    }
+
+    fn assert_roundtrip<T>()
+    where
+        T: Random + Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+    {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let value = T::random(&mut rng);
+
+            let json = serde_json::to_string(&value).unwrap();
+            let deserialized: T = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(deserialized, value, "Mismatch for {}", json);
+        }
+    }
+
+    #[test]
+    fn should_roundtrip_logs() {
+        assert_roundtrip::<Log>();
+    }
+
+    #[test]
+    fn should_roundtrip_transactions() {
+        assert_roundtrip::<Transaction>();
+    }
+
+    #[test]
+    fn should_roundtrip_receipts() {
+        assert_roundtrip::<TransactionReceipt>();
+    }
+
+    #[test]
+    fn should_roundtrip_blocks() {
+        assert_roundtrip::<Block<H256>>();
+        assert_roundtrip::<Block<Transaction>>();
+    }
+
+    #[test]
+    fn should_roundtrip_work() {
+        assert_roundtrip::<Work>();
+    }
+
+    #[test]
+    fn should_roundtrip_blooms() {
+        assert_roundtrip::<Bloom>();
+    }
+}
@@ -0,0 +1,232 @@
+//! Minimal canonical RLP encoding and decoding.
+
+/// Decoded RLP item.
+#[derive(Debug, PartialEq)]
+pub enum Item<'a> {
+    /// Byte string.
+    Bytes(&'a [u8]),
+    /// List of items.
+    List(Vec<Item<'a>>),
+}
+
+/// RLP decoding error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecoderError {
+    /// Input ended before the item.
+    TooShort,
+    /// There are bytes after the item.
+    TrailingBytes,
+    /// Item is not in its shortest form.
+    NonCanonical,
+    /// Got list where bytes were expected or the reverse.
+    UnexpectedType,
+}
+
+/// RLP stream appending items to a buffer.
+#[derive(Debug, Default)]
+pub struct Stream {
+    out: Vec<u8>,
+}
+
+impl Stream {
+    /// Appends byte string.
+    pub fn append_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        if bytes.len() == 1 && bytes[0] < 0x80 {
+            self.out.push(bytes[0]);
+        } else {
+            push_header(&mut self.out, 0x80, bytes.len());
+            self.out.extend_from_slice(bytes);
+        }
+        self
+    }
+
+    /// Appends big-endian number without leading zeros.
+    pub fn append_uint(&mut self, bytes: &[u8]) -> &mut Self {
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        self.append_bytes(&bytes[skip..])
+    }
+
+    /// Appends number without leading zeros.
+    pub fn append_u64(&mut self, value: u64) -> &mut Self {
+        self.append_uint(&value.to_be_bytes())
+    }
+
+    /// Consumes the stream returning its items as a list.
+    pub fn into_list(self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.out.len() + 9);
+        push_header(&mut out, 0xc0, self.out.len());
+        out.extend(self.out);
+        out
+    }
+}
+
+fn push_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len <= 55 {
+        out.push(offset + len as u8);
+    } else {
+        let bytes = (len as u64).to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(offset + 55 + (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+/// Decodes single item spanning the whole input.
+pub fn decode(input: &[u8]) -> Result<Item, DecoderError> {
+    let (item, rest) = decode_item(input)?;
+    if !rest.is_empty() {
+        return Err(DecoderError::TrailingBytes);
+    }
+    Ok(item)
+}
+
+fn decode_item(input: &[u8]) -> Result<(Item, &[u8]), DecoderError> {
+    let first = *input.first().ok_or(DecoderError::TooShort)?;
+    let (is_list, offset, len) = match first {
+        0x00...0x7f => return Ok((Item::Bytes(&input[..1]), &input[1..])),
+        0x80...0xb7 => (false, 1, (first - 0x80) as usize),
+        0xb8...0xbf => {
+            let len_of_len = (first - 0xb7) as usize;
+            (false, 1 + len_of_len, decode_length(&input[1..], len_of_len)?)
+        }
+        0xc0...0xf7 => (true, 1, (first - 0xc0) as usize),
+        _ => {
+            let len_of_len = (first - 0xf7) as usize;
+            (true, 1 + len_of_len, decode_length(&input[1..], len_of_len)?)
+        }
+    };
+
+    let end = offset.checked_add(len).ok_or(DecoderError::TooShort)?;
+    if input.len() < end {
+        return Err(DecoderError::TooShort);
+    }
+    let (payload, rest) = (&input[offset..end], &input[end..]);
+
+    if !is_list {
+        if len == 1 && payload[0] < 0x80 {
+            return Err(DecoderError::NonCanonical);
+        }
+        return Ok((Item::Bytes(payload), rest));
+    }
+
+    let mut items = Vec::new();
+    let mut payload = payload;
+    while !payload.is_empty() {
+        let (item, remaining) = decode_item(payload)?;
+        items.push(item);
+        payload = remaining;
+    }
+    Ok((Item::List(items), rest))
+}
+
+fn decode_length(input: &[u8], len_of_len: usize) -> Result<usize, DecoderError> {
+    if input.len() < len_of_len {
+        return Err(DecoderError::TooShort);
+    }
+    if input[0] == 0 || len_of_len > 8 {
+        return Err(DecoderError::NonCanonical);
+    }
+
+    let len = input[..len_of_len]
+        .iter()
+        .fold(0u64, |len, byte| (len << 8) | *byte as u64);
+    if len <= 55 || len > usize::max_value() as u64 {
+        return Err(DecoderError::NonCanonical);
+    }
+    Ok(len as usize)
+}
+
+impl<'a> Item<'a> {
+    /// Returns the byte string or fails if the item is a list.
+    pub fn as_bytes(&self) -> Result<&'a [u8], DecoderError> {
+        match *self {
+            Item::Bytes(bytes) => Ok(bytes),
+            Item::List(_) => Err(DecoderError::UnexpectedType),
+        }
+    }
+
+    /// Returns big-endian number of at most `max_len` bytes.
+    pub fn as_uint(&self, max_len: usize) -> Result<&'a [u8], DecoderError> {
+        let bytes = self.as_bytes()?;
+        if bytes.first() == Some(&0) {
+            return Err(DecoderError::NonCanonical);
+        }
+        if bytes.len() > max_len {
+            return Err(DecoderError::UnexpectedType);
+        }
+        Ok(bytes)
+    }
+
+    /// Returns number of at most 8 bytes.
+    pub fn as_u64(&self) -> Result<u64, DecoderError> {
+        let bytes = self.as_uint(8)?;
+        Ok(bytes.iter().fold(0u64, |value, byte| (value << 8) | *byte as u64))
+    }
+
+    /// Returns items of the list or fails if the item is a byte string.
+    pub fn as_list(&self) -> Result<&[Item<'a>], DecoderError> {
+        match *self {
+            Item::List(ref items) => Ok(items),
+            Item::Bytes(_) => Err(DecoderError::UnexpectedType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, DecoderError, Item, Stream};
+
+    #[test]
+    fn should_encode_items() {
+        let mut stream = Stream::default();
+        stream
+            .append_bytes(b"dog")
+            .append_bytes(&[0x7f])
+            .append_bytes(&[])
+            .append_uint(&[0, 0, 0x04, 0x00])
+            .append_u64(0);
+
+        assert_eq!(
+            stream.into_list(),
+            vec![0xc9, 0x83, b'd', b'o', b'g', 0x7f, 0x80, 0x82, 0x04, 0x00, 0x80]
+        );
+    }
+
+    #[test]
+    fn should_encode_long_strings() {
+        let data = vec![b'a'; 56];
+        let mut stream = Stream::default();
+        stream.append_bytes(&data);
+
+        let encoded = stream.into_list();
+
+        assert_eq!(&encoded[..3], &[0xf8, 58, 0xb8]);
+        assert_eq!(encoded[3], 56);
+        assert_eq!(decode(&encoded), Ok(Item::List(vec![Item::Bytes(&data)])));
+    }
+
+    #[test]
+    fn should_decode_nested_lists() {
+        let encoded = [0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0];
+
+        let empty = || Item::List(vec![]);
+        assert_eq!(
+            decode(&encoded),
+            Ok(Item::List(vec![
+                empty(),
+                Item::List(vec![empty()]),
+                Item::List(vec![empty(), Item::List(vec![empty()])]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_input() {
+        assert_eq!(decode(&[]), Err(DecoderError::TooShort));
+        assert_eq!(decode(&[0x83, b'd', b'o']), Err(DecoderError::TooShort));
+        assert_eq!(decode(&[0x81, 0x05]), Err(DecoderError::NonCanonical));
+        assert_eq!(decode(&[0xb8, 0x02, 0x00, 0x00]), Err(DecoderError::NonCanonical));
+        assert_eq!(decode(&[0x80, 0x80]), Err(DecoderError::TrailingBytes));
+        assert_eq!(Item::Bytes(&[0, 1]).as_uint(32), Err(DecoderError::NonCanonical));
+    }
+}
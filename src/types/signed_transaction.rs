@@ -0,0 +1,311 @@
+//! Locally signed Ethereum transactions.
+
+use std::fmt;
+use cita_crypto::{PrivKey, Sign, Signature};
+use tiny_keccak::keccak256;
+use util;
+use types::rlp::{self, DecoderError, Item, Stream};
+use types::{Address, Bytes, TransactionRequest, H256, U256};
+
+/// Transaction signing or decoding error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningError {
+    /// Request is missing a field required for signing.
+    MissingField(&'static str),
+    /// Secp256k1 failed to sign or recover the signature.
+    Crypto(String),
+    /// Invalid RLP encoding of the transaction.
+    Decoder(DecoderError),
+    /// Signature `v` value is neither legacy nor EIP-155.
+    InvalidV(u64),
+    /// EIP-155 chain id is too large to be encoded in `v`.
+    InvalidChainId(u64),
+}
+
+impl fmt::Display for SigningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SigningError::MissingField(field) => write!(f, "Missing transaction field: {}", field),
+            SigningError::Crypto(ref err) => write!(f, "Signature error: {}", err),
+            SigningError::Decoder(ref err) => write!(f, "Invalid transaction encoding: {:?}", err),
+            SigningError::InvalidV(v) => write!(f, "Invalid signature v: {}", v),
+            SigningError::InvalidChainId(chain_id) => write!(f, "Invalid chain id: {}", chain_id),
+        }
+    }
+}
+
+impl From<DecoderError> for SigningError {
+    fn from(err: DecoderError) -> Self {
+        SigningError::Decoder(err)
+    }
+}
+
+/// Unsigned Ethereum transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawTransaction {
+    /// Nonce
+    pub nonce: U256,
+    /// Gas price
+    pub gas_price: U256,
+    /// Gas limit
+    pub gas: U256,
+    /// Recipient address (None for contract creation)
+    pub to: Option<Address>,
+    /// Transfered value
+    pub value: U256,
+    /// Transaction data
+    pub data: Bytes,
+}
+
+impl RawTransaction {
+    /// Creates transaction from a request with `nonce`, `gas` and `gas_price` filled in.
+    pub fn from_request(request: &TransactionRequest) -> Result<Self, SigningError> {
+        Ok(RawTransaction {
+            nonce: request.nonce.ok_or(SigningError::MissingField("nonce"))?,
+            gas_price: request
+                .gas_price
+                .ok_or(SigningError::MissingField("gasPrice"))?,
+            gas: request.gas.ok_or(SigningError::MissingField("gas"))?,
+            to: request.to,
+            value: request.value.unwrap_or_default(),
+            data: request.data.clone().unwrap_or_default(),
+        })
+    }
+
+    fn rlp_append(&self, stream: &mut Stream) {
+        stream
+            .append_uint(&self.nonce)
+            .append_uint(&self.gas_price)
+            .append_uint(&self.gas)
+            .append_bytes(self.to.as_ref().map_or(&[][..], |to| &to[..]))
+            .append_uint(&self.value)
+            .append_bytes(&self.data.0);
+    }
+
+    /// Returns hash signed by the sender, replay protected if `chain_id` is given (EIP-155).
+    pub fn signing_hash(&self, chain_id: Option<u64>) -> H256 {
+        let mut stream = Stream::default();
+        self.rlp_append(&mut stream);
+        if let Some(chain_id) = chain_id {
+            stream.append_u64(chain_id).append_bytes(&[]).append_bytes(&[]);
+        }
+        H256(keccak256(&stream.into_list()))
+    }
+
+    /// Signs the transaction with given secp256k1 key.
+    pub fn sign(self, key: &PrivKey, chain_id: Option<u64>) -> Result<SignedTransaction, SigningError> {
+        let hash: util::H256 = self.signing_hash(chain_id).into();
+        let signature = Signature::sign(key, &hash).map_err(|err| SigningError::Crypto(format!("{:?}", err)))?;
+
+        let recovery_id = signature.v() as u64;
+        Ok(SignedTransaction {
+            transaction: self,
+            v: match chain_id {
+                Some(chain_id) => chain_id
+                    .checked_mul(2)
+                    .and_then(|v| v.checked_add(35 + recovery_id))
+                    .ok_or(SigningError::InvalidChainId(chain_id))?,
+                None => recovery_id + 27,
+            },
+            r: H256::from(signature.r()),
+            s: H256::from(signature.s()),
+        })
+    }
+}
+
+/// Signed Ethereum transaction ready for `eth_sendRawTransaction`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedTransaction {
+    /// Unsigned transaction
+    pub transaction: RawTransaction,
+    /// Signature `v` (recovery id with EIP-155 chain id)
+    pub v: u64,
+    /// Signature `r`
+    pub r: H256,
+    /// Signature `s`
+    pub s: H256,
+}
+
+impl SignedTransaction {
+    /// Decodes RLP-encoded signed transaction.
+    pub fn decode(raw: &[u8]) -> Result<Self, SigningError> {
+        let item = rlp::decode(raw)?;
+        let fields = item.as_list()?;
+        if fields.len() != 9 {
+            return Err(DecoderError::UnexpectedType.into());
+        }
+
+        let uint = |item: &Item| item.as_uint(32).map(U256::from);
+        let to = fields[3].as_bytes()?;
+        let transaction = RawTransaction {
+            nonce: uint(&fields[0])?,
+            gas_price: uint(&fields[1])?,
+            gas: uint(&fields[2])?,
+            to: match to.len() {
+                0 => None,
+                20 => Some(Address::from(to)),
+                _ => return Err(DecoderError::UnexpectedType.into()),
+            },
+            value: uint(&fields[4])?,
+            data: fields[5].as_bytes()?.into(),
+        };
+
+        let signed = SignedTransaction {
+            transaction,
+            v: fields[6].as_u64()?,
+            r: fields[7].as_uint(32).map(H256::from)?,
+            s: fields[8].as_uint(32).map(H256::from)?,
+        };
+        signed.recovery_id()?;
+        Ok(signed)
+    }
+
+    /// Returns EIP-155 chain id or `None` for transactions without replay protection.
+    pub fn chain_id(&self) -> Option<u64> {
+        if self.v >= 35 {
+            Some((self.v - 35) / 2)
+        } else {
+            None
+        }
+    }
+
+    fn recovery_id(&self) -> Result<u8, SigningError> {
+        match self.v {
+            27 | 28 => Ok((self.v - 27) as u8),
+            v if v >= 35 => Ok(((v - 35) % 2) as u8),
+            v => Err(SigningError::InvalidV(v)),
+        }
+    }
+
+    /// Returns RLP-encoded transaction.
+    pub fn raw(&self) -> Bytes {
+        let mut stream = Stream::default();
+        self.transaction.rlp_append(&mut stream);
+        stream
+            .append_u64(self.v)
+            .append_uint(&self.r)
+            .append_uint(&self.s);
+        stream.into_list().into()
+    }
+
+    /// Returns transaction hash.
+    pub fn hash(&self) -> H256 {
+        H256(keccak256(&self.raw().0))
+    }
+
+    /// Recovers address of the signer.
+    pub fn sender(&self) -> Result<Address, SigningError> {
+        let hash: util::H256 = self.transaction.signing_hash(self.chain_id()).into();
+        let signature = Signature::from_rsv(&self.r.into(), &self.s.into(), self.recovery_id()?);
+        let public = signature
+            .recover(&hash)
+            .map_err(|err| SigningError::Crypto(format!("{:?}", err)))?;
+
+        Ok(Address::from(&keccak256(&public[..])[12..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hex::FromHex;
+    use types::{Address, Bytes, TransactionRequest, H256, U256};
+    use super::{RawTransaction, SignedTransaction, SigningError};
+
+    // Example from EIP-155
+    fn transaction() -> RawTransaction {
+        RawTransaction {
+            nonce: 9.into(),
+            gas_price: U256::from(20_000_000_000u64),
+            gas: 21_000.into(),
+            to: Some(Address::from_hex_str("0x3535353535353535353535353535353535353535").unwrap()),
+            value: U256::from(10).pow(18),
+            data: Bytes::default(),
+        }
+    }
+
+    const SIGNED: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+                          8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f\
+                          761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    #[test]
+    fn should_sign_with_replay_protection() {
+        // given
+        let key = H256::from_hex_str("0x4646464646464646464646464646464646464646464646464646464646464646").unwrap();
+
+        // when
+        let hash = transaction().signing_hash(Some(1));
+        let signed = transaction().sign(&key.into(), Some(1)).unwrap();
+
+        // then
+        assert_eq!(
+            hash,
+            H256::from_hex_str("0xdaf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53").unwrap()
+        );
+        assert_eq!(signed.v, 37);
+        assert_eq!(signed.chain_id(), Some(1));
+        assert_eq!(signed.raw().0, SIGNED.from_hex().unwrap());
+    }
+
+    #[test]
+    fn should_decode_signed_transaction() {
+        // given
+        let raw: Vec<u8> = SIGNED.from_hex().unwrap();
+
+        // when
+        let signed = SignedTransaction::decode(&raw).unwrap();
+
+        // then
+        assert_eq!(signed.transaction, transaction());
+        assert_eq!(signed.raw().0, raw);
+        assert_eq!(
+            signed.sender(),
+            Ok(Address::from_hex_str("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap())
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_legacy_contract_creation() {
+        let key = H256::from(1);
+        let mut transaction = transaction();
+        transaction.to = None;
+        transaction.data = vec![0x60, 0x80].into();
+
+        let signed = transaction.clone().sign(&key.into(), None).unwrap();
+        let decoded = SignedTransaction::decode(&signed.raw().0).unwrap();
+
+        assert!(signed.v == 27 || signed.v == 28);
+        assert_eq!(decoded.chain_id(), None);
+        assert_eq!(decoded, signed);
+        assert_eq!(decoded.sender(), signed.sender());
+    }
+
+    #[test]
+    fn should_reject_too_large_chain_id() {
+        let key = H256::from(1);
+        let chain_id = u64::max_value() / 2;
+
+        assert_eq!(
+            transaction().sign(&key.into(), Some(chain_id)),
+            Err(SigningError::InvalidChainId(chain_id))
+        );
+    }
+
+    #[test]
+    fn should_require_nonce_and_gas() {
+        let request = TransactionRequest {
+            from: Address::from(1),
+            to: None,
+            gas: Some(21_000.into()),
+            gas_price: Some(1.into()),
+            value: None,
+            data: None,
+            nonce: None,
+            condition: None,
+        };
+
+        assert_eq!(
+            RawTransaction::from_request(&request),
+            Err(SigningError::MissingField("nonce"))
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use types::U256;
+
+/// Sync status returned by `eth_syncing`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncState {
+    /// Node is synced (`false`).
+    NotSyncing,
+    /// Node is importing blocks.
+    Syncing {
+        /// Block at which the import started.
+        starting_block: U256,
+        /// Current block.
+        current_block: U256,
+        /// Highest known block.
+        highest_block: U256,
+        /// Known state entries (only during state sync).
+        known_states: Option<U256>,
+        /// Downloaded state entries (only during state sync).
+        pulled_states: Option<U256>,
+    },
+}
+
+impl SyncState {
+    /// Returns `true` if the node is still importing blocks.
+    pub fn is_syncing(&self) -> bool {
+        *self != SyncState::NotSyncing
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SyncInfo {
+    #[serde(rename = "startingBlock")]
+    starting_block: U256,
+    #[serde(rename = "currentBlock")]
+    current_block: U256,
+    #[serde(rename = "highestBlock")]
+    highest_block: U256,
+    #[serde(rename = "knownStates", default, skip_serializing_if = "Option::is_none")]
+    known_states: Option<U256>,
+    #[serde(rename = "pulledStates", default, skip_serializing_if = "Option::is_none")]
+    pulled_states: Option<U256>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSyncState {
+    Syncing(SyncInfo),
+    NotSyncing(bool),
+}
+
+impl Serialize for SyncState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            SyncState::NotSyncing => serializer.serialize_bool(false),
+            SyncState::Syncing {
+                starting_block,
+                current_block,
+                highest_block,
+                known_states,
+                pulled_states,
+            } => SyncInfo {
+                starting_block,
+                current_block,
+                highest_block,
+                known_states,
+                pulled_states,
+            }.serialize(serializer),
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for SyncState {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        match RawSyncState::deserialize(deserializer)? {
+            RawSyncState::NotSyncing(false) => Ok(SyncState::NotSyncing),
+            RawSyncState::NotSyncing(true) => Err(D::Error::custom("expected `false` or sync status object")),
+            RawSyncState::Syncing(info) => Ok(SyncState::Syncing {
+                starting_block: info.starting_block,
+                current_block: info.current_block,
+                highest_block: info.highest_block,
+                known_states: info.known_states,
+                pulled_states: info.pulled_states,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use types::U256;
+    use super::SyncState;
+
+    #[test]
+    fn should_deserialize_sync_state() {
+        let syncing = r#"{"startingBlock":"0x384","currentBlock":"0x386","highestBlock":"0x454"}"#;
+
+        assert_eq!(serde_json::from_str::<SyncState>("false").unwrap(), SyncState::NotSyncing);
+        assert_eq!(
+            serde_json::from_str::<SyncState>(syncing).unwrap(),
+            SyncState::Syncing {
+                starting_block: 0x384.into(),
+                current_block: 0x386.into(),
+                highest_block: 0x454.into(),
+                known_states: None,
+                pulled_states: None,
+            }
+        );
+        assert!(serde_json::from_str::<SyncState>("true").is_err());
+    }
+
+    #[test]
+    fn should_roundtrip_sync_state() {
+        let state = SyncState::Syncing {
+            starting_block: U256::zero(),
+            current_block: 5.into(),
+            highest_block: 10.into(),
+            known_states: Some(100.into()),
+            pulled_states: Some(50.into()),
+        };
+
+        for state in vec![state, SyncState::NotSyncing] {
+            let json = serde_json::to_string(&state).unwrap();
+
+            assert_eq!(serde_json::from_str::<SyncState>(&json).unwrap(), state);
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use types::{Bytes, H160, H256, Index, Log, U256};
+use types::{Bloom, Bytes, H160, H256, Index, Log, U256, U64};
 
 /// Description of a Transaction, pending or in the chain.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transaction {
     /// Hash
     pub hash: H256,
@@ -57,6 +57,22 @@ pub struct Receipt {
     pub contract_address: Option<H160>,
     /// Logs generated within this transaction.
     pub logs: Vec<Log>,
+    /// Bloom of the logs (empty if the node doesn't report it).
+    #[serde(rename = "logsBloom", default)]
+    pub logs_bloom: Bloom,
+    /// Status: `1` for success, `0` for failure (EIP-658, `None` before Byzantium).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<U64>,
+    /// State root (`None` since Byzantium).
+    #[serde(default)]
+    pub root: Option<H256>,
+}
+
+impl Receipt {
+    /// Returns `true` if the receipt reports failed execution (`status == 0`).
+    pub fn is_failed(&self) -> bool {
+        self.status.map_or(false, |status| status.is_zero())
+    }
 }
 
 #[cfg(test)]
@@ -70,4 +86,15 @@ mod tests {
 
         let _receipt: Receipt = serde_json::from_str(receipt_str).unwrap();
     }
+
+    #[test]
+    fn should_deserialize_receipt_status() {
+        let receipt_str = r#"{"blockHash":"0x83eaba432089a0bfe99e9fc9022d1cfcb78f95f407821be81737c84ae0b439c5","blockNumber":"0x38","contractAddress":null,"cumulativeGasUsed":"0x927c0","gasUsed":"0x927c0","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","status":"0x0","transactionHash":"0x422fb0d5953c0c48cbb42fb58e1c30f5e150441c68374d70ca7d4f191fd56f26","transactionIndex":"0x0"}"#;
+
+        let receipt: Receipt = serde_json::from_str(receipt_str).unwrap();
+
+        assert_eq!(receipt.status, Some(0.into()));
+        assert_eq!(receipt.root, None);
+        assert!(receipt.is_failed());
+    }
 }
@@ -10,17 +10,613 @@ pub enum FromStrError {
     InvalidLength { got: usize, expected: usize },
     InvalidPrefix,
     InvalidCharacter(char),
+    Overflow,
+    InvalidChecksum,
+}
+
+/// Arithmetic on little-endian 64-bit limbs.
+mod limbs {
+    use std::cmp;
+
+    pub fn from_be(bytes: &[u8], limbs: &mut [u64]) {
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = 0;
+            for j in 0..8 {
+                *limb |= (bytes[bytes.len() - 1 - i * 8 - j] as u64) << (j * 8);
+            }
+        }
+    }
+
+    pub fn to_be(limbs: &[u64], bytes: &mut [u8]) {
+        let len = bytes.len();
+        for (i, limb) in limbs.iter().enumerate() {
+            for j in 0..8 {
+                bytes[len - 1 - i * 8 - j] = (limb >> (j * 8)) as u8;
+            }
+        }
+    }
+
+    pub fn cmp(a: &[u64], b: &[u64]) -> cmp::Ordering {
+        for i in (0..a.len()).rev() {
+            match a[i].cmp(&b[i]) {
+                cmp::Ordering::Equal => {}
+                other => return other,
+            }
+        }
+        cmp::Ordering::Equal
+    }
+
+    /// Adds `b` to `a`, returns the carry.
+    pub fn add(a: &mut [u64], b: &[u64]) -> bool {
+        let mut carry = false;
+        for i in 0..a.len() {
+            let (sum, c1) = a[i].overflowing_add(b[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            a[i] = sum;
+            carry = c1 || c2;
+        }
+        carry
+    }
+
+    /// Subtracts `b` from `a`, returns the borrow.
+    pub fn sub(a: &mut [u64], b: &[u64]) -> bool {
+        let mut borrow = false;
+        for i in 0..a.len() {
+            let (diff, b1) = a[i].overflowing_sub(b[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            a[i] = diff;
+            borrow = b1 || b2;
+        }
+        borrow
+    }
+
+    /// Writes lower half of `a * b` to `out`, returns `true` on overflow.
+    pub fn mul(a: &[u64], b: &[u64], out: &mut [u64]) -> bool {
+        let n = a.len();
+        let mut overflow = false;
+        for limb in out.iter_mut() {
+            *limb = 0;
+        }
+
+        for i in 0..n {
+            if a[i] == 0 {
+                continue;
+            }
+
+            let mut carry = 0u128;
+            for j in 0..n - i {
+                let sum = out[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+                out[i + j] = sum as u64;
+                carry = sum >> 64;
+            }
+            overflow |= carry != 0 || b[n - i..].iter().any(|limb| *limb != 0);
+        }
+        overflow
+    }
+
+    /// Multiplies `a` by `m` and adds `add`, returns the carry.
+    pub fn mul_small(a: &mut [u64], m: u64, add: u64) -> u64 {
+        let mut carry = add as u128;
+        for limb in a.iter_mut() {
+            let value = *limb as u128 * m as u128 + carry;
+            *limb = value as u64;
+            carry = value >> 64;
+        }
+        carry as u64
+    }
+
+    /// Divides `a` by `d` in place, returns the remainder.
+    pub fn div_small(a: &mut [u64], d: u64) -> u64 {
+        let mut rem = 0u128;
+        for limb in a.iter_mut().rev() {
+            let value = (rem << 64) | *limb as u128;
+            *limb = (value / d as u128) as u64;
+            rem = value % d as u128;
+        }
+        rem as u64
+    }
+
+    /// Writes `a / b` to `q` and `a % b` to `r`. `b` must not be zero.
+    pub fn div_mod(a: &[u64], b: &[u64], q: &mut [u64], r: &mut [u64]) {
+        for i in 0..q.len() {
+            q[i] = 0;
+            r[i] = 0;
+        }
+
+        for bit in (0..a.len() * 64).rev() {
+            let carry = shl(r, 1);
+            r[0] |= (a[bit / 64] >> (bit % 64)) & 1;
+            if carry || cmp(r, b) != cmp::Ordering::Less {
+                sub(r, b);
+                q[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+
+    /// Shifts `a` left in place, returns `true` if any non-zero bit was shifted out.
+    pub fn shl(a: &mut [u64], shift: usize) -> bool {
+        let n = a.len();
+        let lost = if shift >= n * 64 {
+            a.iter().any(|limb| *limb != 0)
+        } else {
+            (n * 64 - shift..n * 64).any(|bit| (a[bit / 64] >> (bit % 64)) & 1 == 1)
+        };
+
+        let (limbs, bits) = (shift / 64, shift % 64);
+        for i in (0..n).rev() {
+            let mut value = 0;
+            if i >= limbs {
+                value = a[i - limbs] << bits;
+                if bits > 0 && i > limbs {
+                    value |= a[i - limbs - 1] >> (64 - bits);
+                }
+            }
+            a[i] = value;
+        }
+        lost
+    }
+
+    /// Shifts `a` right in place.
+    pub fn shr(a: &mut [u64], shift: usize) {
+        let n = a.len();
+        let (limbs, bits) = (shift / 64, shift % 64);
+        for i in 0..n {
+            let mut value = 0;
+            if i + limbs < n {
+                value = a[i + limbs] >> bits;
+                if bits > 0 && i + limbs + 1 < n {
+                    value |= a[i + limbs + 1] << (64 - bits);
+                }
+            }
+            a[i] = value;
+        }
+    }
+}
+
+macro_rules! impl_uint_arith {
+  ($name: ident, $len: expr) => {
+    impl $name {
+      fn limbs(&self) -> [u64; $len / 8] {
+        let mut limbs = [0; $len / 8];
+        limbs::from_be(&self.0, &mut limbs);
+        limbs
+      }
+
+      fn from_limbs(limbs: &[u64]) -> Self {
+        let mut arr = [0; $len];
+        limbs::to_be(limbs, &mut arr);
+        $name(arr)
+      }
+
+      /// Zero value
+      pub fn zero() -> Self {
+        Self::default()
+      }
+
+      /// Largest representable value
+      pub fn max_value() -> Self {
+        $name([0xff; $len])
+      }
+
+      /// Returns `true` if the value is zero
+      pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+      }
+
+      /// Returns number of significant bits
+      pub fn bits(&self) -> usize {
+        match self.0.iter().position(|byte| *byte != 0) {
+          Some(idx) => ($len - idx) * 8 - self.0[idx].leading_zeros() as usize,
+          None => 0,
+        }
+      }
+
+      /// Returns the value if it fits in `u64`
+      pub fn to_u64(&self) -> Option<u64> {
+        if self.bits() > 64 {
+          None
+        } else {
+          Some(self.low_u64())
+        }
+      }
+
+      /// Creates value from big-endian bytes, returns `None` if it doesn't fit.
+      pub fn from_big_endian(bytes: &[u8]) -> Option<Self> {
+        let significant = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+        if bytes.len() - significant > $len {
+          return None;
+        }
+        Some($name::from(&bytes[significant..]))
+      }
+
+      /// Returns big-endian bytes
+      pub fn to_big_endian(&self) -> [u8; $len] {
+        self.0
+      }
+
+      /// Addition returning the wrapped result and `true` on overflow
+      pub fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let mut a = self.limbs();
+        let overflow = limbs::add(&mut a, &other.limbs());
+        (Self::from_limbs(&a), overflow)
+      }
+
+      /// Subtraction returning the wrapped result and `true` on overflow
+      pub fn overflowing_sub(self, other: Self) -> (Self, bool) {
+        let mut a = self.limbs();
+        let overflow = limbs::sub(&mut a, &other.limbs());
+        (Self::from_limbs(&a), overflow)
+      }
+
+      /// Multiplication returning the wrapped result and `true` on overflow
+      pub fn overflowing_mul(self, other: Self) -> (Self, bool) {
+        let mut out = [0; $len / 8];
+        let overflow = limbs::mul(&self.limbs(), &other.limbs(), &mut out);
+        (Self::from_limbs(&out), overflow)
+      }
+
+      /// Exponentiation returning the wrapped result and `true` on overflow
+      pub fn overflowing_pow(self, mut exp: u32) -> (Self, bool) {
+        let mut base = self;
+        let mut result = Self::from(1u64);
+        let mut overflow = false;
+        while exp > 0 {
+          if exp & 1 == 1 {
+            let (value, o) = result.overflowing_mul(base);
+            result = value;
+            overflow |= o;
+          }
+          exp >>= 1;
+          if exp > 0 {
+            let (value, o) = base.overflowing_mul(base);
+            base = value;
+            overflow |= o;
+          }
+        }
+        (result, overflow)
+      }
+
+      /// Returns quotient and remainder. Panics if `other` is zero.
+      pub fn div_mod(self, other: Self) -> (Self, Self) {
+        assert!(!other.is_zero(), "attempt to divide by zero");
+        let mut q = [0; $len / 8];
+        let mut r = [0; $len / 8];
+        limbs::div_mod(&self.limbs(), &other.limbs(), &mut q, &mut r);
+        (Self::from_limbs(&q), Self::from_limbs(&r))
+      }
+
+      /// Checked addition, `None` on overflow
+      pub fn checked_add(self, other: Self) -> Option<Self> {
+        match self.overflowing_add(other) {
+          (_, true) => None,
+          (value, false) => Some(value),
+        }
+      }
+
+      /// Checked subtraction, `None` on underflow
+      pub fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.overflowing_sub(other) {
+          (_, true) => None,
+          (value, false) => Some(value),
+        }
+      }
+
+      /// Checked multiplication, `None` on overflow
+      pub fn checked_mul(self, other: Self) -> Option<Self> {
+        match self.overflowing_mul(other) {
+          (_, true) => None,
+          (value, false) => Some(value),
+        }
+      }
+
+      /// Checked division, `None` if `other` is zero
+      pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+          None
+        } else {
+          Some(self.div_mod(other).0)
+        }
+      }
+
+      /// Checked remainder, `None` if `other` is zero
+      pub fn checked_rem(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+          None
+        } else {
+          Some(self.div_mod(other).1)
+        }
+      }
+
+      /// Checked exponentiation, `None` on overflow
+      pub fn checked_pow(self, exp: u32) -> Option<Self> {
+        match self.overflowing_pow(exp) {
+          (_, true) => None,
+          (value, false) => Some(value),
+        }
+      }
+
+      /// Checked shift left, `None` if `shift` is not smaller than the number of bits
+      pub fn checked_shl(self, shift: u32) -> Option<Self> {
+        if shift as usize >= $len * 8 {
+          None
+        } else {
+          Some(self << shift as usize)
+        }
+      }
+
+      /// Checked shift right, `None` if `shift` is not smaller than the number of bits
+      pub fn checked_shr(self, shift: u32) -> Option<Self> {
+        if shift as usize >= $len * 8 {
+          None
+        } else {
+          Some(self >> shift as usize)
+        }
+      }
+
+      /// Wrapping (modular) addition
+      pub fn wrapping_add(self, other: Self) -> Self {
+        self.overflowing_add(other).0
+      }
+
+      /// Wrapping (modular) subtraction
+      pub fn wrapping_sub(self, other: Self) -> Self {
+        self.overflowing_sub(other).0
+      }
+
+      /// Wrapping (modular) multiplication
+      pub fn wrapping_mul(self, other: Self) -> Self {
+        self.overflowing_mul(other).0
+      }
+
+      /// Wrapping division (never wraps for unsigned values). Panics if `other` is zero.
+      pub fn wrapping_div(self, other: Self) -> Self {
+        self.div_mod(other).0
+      }
+
+      /// Wrapping remainder (never wraps for unsigned values). Panics if `other` is zero.
+      pub fn wrapping_rem(self, other: Self) -> Self {
+        self.div_mod(other).1
+      }
+
+      /// Wrapping (modular) exponentiation
+      pub fn wrapping_pow(self, exp: u32) -> Self {
+        self.overflowing_pow(exp).0
+      }
+
+      /// Saturating addition
+      pub fn saturating_add(self, other: Self) -> Self {
+        self.checked_add(other).unwrap_or_else(Self::max_value)
+      }
+
+      /// Saturating subtraction
+      pub fn saturating_sub(self, other: Self) -> Self {
+        self.checked_sub(other).unwrap_or_else(Self::zero)
+      }
+
+      /// Saturating multiplication
+      pub fn saturating_mul(self, other: Self) -> Self {
+        self.checked_mul(other).unwrap_or_else(Self::max_value)
+      }
+
+      /// Saturating division (never saturates for unsigned values). Panics if `other` is zero.
+      pub fn saturating_div(self, other: Self) -> Self {
+        self.div_mod(other).0
+      }
+
+      /// Saturating remainder (never saturates for unsigned values). Panics if `other` is zero.
+      pub fn saturating_rem(self, other: Self) -> Self {
+        self.div_mod(other).1
+      }
+
+      /// Saturating exponentiation
+      pub fn saturating_pow(self, exp: u32) -> Self {
+        self.checked_pow(exp).unwrap_or_else(Self::max_value)
+      }
+
+      /// Exponentiation. Panics on overflow.
+      pub fn pow(self, exp: u32) -> Self {
+        self.checked_pow(exp).expect("attempt to exponentiate with overflow")
+      }
+
+      /// Parses decimal string
+      pub fn from_dec_str(s: &str) -> Result<Self, FromStrError> {
+        if s.is_empty() {
+          return Err(FromStrError::InvalidLength { got: 0, expected: 1 });
+        }
+
+        let mut limbs = [0; $len / 8];
+        for c in s.chars() {
+          let digit = match c.to_digit(10) {
+            Some(digit) => digit as u64,
+            None => return Err(FromStrError::InvalidCharacter(c)),
+          };
+          if limbs::mul_small(&mut limbs, 10, digit) != 0 {
+            return Err(FromStrError::Overflow);
+          }
+        }
+        Ok(Self::from_limbs(&limbs))
+      }
+
+      /// Returns decimal representation
+      pub fn to_dec_string(&self) -> String {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        let mut limbs = self.limbs();
+        let mut chunks = vec![];
+        loop {
+          chunks.push(limbs::div_small(&mut limbs, CHUNK));
+          if limbs.iter().all(|limb| *limb == 0) {
+            break;
+          }
+        }
+
+        let mut result = chunks.pop().map(|chunk| chunk.to_string()).unwrap_or_default();
+        for chunk in chunks.iter().rev() {
+          result.push_str(&format!("{:019}", chunk));
+        }
+        result
+      }
+    }
+
+    impl ops::Add for $name {
+      type Output = Self;
+
+      fn add(self, other: Self) -> Self {
+        self.checked_add(other).expect("attempt to add with overflow")
+      }
+    }
+
+    impl ops::Sub for $name {
+      type Output = Self;
+
+      fn sub(self, other: Self) -> Self {
+        self.checked_sub(other).expect("attempt to subtract with overflow")
+      }
+    }
+
+    impl ops::Mul for $name {
+      type Output = Self;
+
+      fn mul(self, other: Self) -> Self {
+        self.checked_mul(other).expect("attempt to multiply with overflow")
+      }
+    }
+
+    impl ops::Div for $name {
+      type Output = Self;
+
+      fn div(self, other: Self) -> Self {
+        self.div_mod(other).0
+      }
+    }
+
+    impl ops::Rem for $name {
+      type Output = Self;
+
+      fn rem(self, other: Self) -> Self {
+        self.div_mod(other).1
+      }
+    }
+
+    impl ops::AddAssign for $name {
+      fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+      }
+    }
+
+    impl ops::SubAssign for $name {
+      fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+      }
+    }
+
+    impl ops::MulAssign for $name {
+      fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+      }
+    }
+
+    impl ops::DivAssign for $name {
+      fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+      }
+    }
+
+    impl ops::RemAssign for $name {
+      fn rem_assign(&mut self, other: Self) {
+        *self = *self % other;
+      }
+    }
+
+    /// Shifts left, bits shifted out are discarded.
+    impl ops::Shl<usize> for $name {
+      type Output = Self;
+
+      fn shl(self, shift: usize) -> Self {
+        let mut limbs = self.limbs();
+        limbs::shl(&mut limbs, shift);
+        Self::from_limbs(&limbs)
+      }
+    }
+
+    /// Shifts right, bits shifted out are discarded.
+    impl ops::Shr<usize> for $name {
+      type Output = Self;
+
+      fn shr(self, shift: usize) -> Self {
+        let mut limbs = self.limbs();
+        limbs::shr(&mut limbs, shift);
+        Self::from_limbs(&limbs)
+      }
+    }
+
+    impl ops::BitAnd for $name {
+      type Output = Self;
+
+      fn bitand(mut self, other: Self) -> Self {
+        for i in 0..$len {
+          self.0[i] &= other.0[i];
+        }
+        self
+      }
+    }
+
+    impl ops::BitOr for $name {
+      type Output = Self;
+
+      fn bitor(mut self, other: Self) -> Self {
+        for i in 0..$len {
+          self.0[i] |= other.0[i];
+        }
+        self
+      }
+    }
+
+    impl ops::BitXor for $name {
+      type Output = Self;
+
+      fn bitxor(mut self, other: Self) -> Self {
+        for i in 0..$len {
+          self.0[i] ^= other.0[i];
+        }
+        self
+      }
+    }
+
+    impl ops::Not for $name {
+      type Output = Self;
+
+      fn not(mut self) -> Self {
+        for i in 0..$len {
+          self.0[i] = !self.0[i];
+        }
+        self
+      }
+    }
+  };
 }
 
 macro_rules! impl_uint {
   ($name: ident, $len: expr) => {
     impl_uint!($name, $len, false);
+    impl_uint_arith!($name, $len);
 
     impl fmt::Display for $name {
       fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // TODO [ToDr] Decimal?
-        write!(f, "0x")?;
-        fmt::LowerHex::fmt(self, f)
+        f.pad_integral(true, "", &self.to_dec_string())
+      }
+    }
+
+    impl FromStr for $name {
+      type Err = FromStrError;
+
+      /// Parses `0x`-prefixed hex or decimal number.
+      fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+          Self::from_hex_str(s)
+        } else {
+          Self::from_dec_str(s)
+        }
       }
     }
   };
@@ -41,6 +637,14 @@ macro_rules! impl_uint {
         Ok(())
       }
     }
+
+    impl FromStr for $name {
+      type Err = FromStrError;
+
+      fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex_str(s)
+      }
+    }
   };
 
   ($name: ident, $len: expr, $strict: expr) => {
@@ -137,15 +741,14 @@ macro_rules! impl_uint {
       fn from(x: &'a [u8]) -> Self {
         let mut arr = [0; $len];
         let len = cmp::min(x.len(), $len);
-        arr[$len - len .. ].copy_from_slice(&x[x.len() - $len .. ]);
+        arr[$len - len .. ].copy_from_slice(&x[x.len() - len .. ]);
         $name(arr)
       }
     }
 
-    impl FromStr for $name {
-      type Err = FromStrError;
-
-      fn from_str(s: &str) -> Result<Self, Self::Err> {
+    impl $name {
+      /// Parses `0x`-prefixed hex string.
+      pub fn from_hex_str(s: &str) -> Result<Self, FromStrError> {
         let strict_len = $strict;
         let len = s.len();
         let expected = $len * 2 + PREFIX;
@@ -226,7 +829,7 @@ macro_rules! impl_uint {
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: serde::de::Error {
-              value.parse().map_err(|e| E::custom(format!("Invalid hex value: {:?}", e)))
+              $name::from_hex_str(value).map_err(|e| E::custom(format!("Invalid hex value: {:?}", e)))
             }
 
             fn visit_string<E>(self, value: String) -> Result<Self::Value, E> where E: serde::de::Error {
@@ -251,9 +854,107 @@ impl_uint!(hash => H512, 64);
 impl_uint!(hash => H520, 65);
 impl_uint!(hash => H2048, 256);
 
+impl U256 {
+    /// Creates value from `u128`
+    pub fn from_u128(value: u128) -> Self {
+        Self::from_limbs(&[value as u64, (value >> 64) as u64, 0, 0])
+    }
+
+    /// Returns value created from lowest 16 bytes
+    pub fn low_u128(&self) -> u128 {
+        let limbs = self.limbs();
+        (limbs[1] as u128) << 64 | limbs[0] as u128
+    }
+
+    /// Returns the value if it fits in `u128`
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.bits() > 128 {
+            None
+        } else {
+            Some(self.low_u128())
+        }
+    }
+}
+
+/// Serialization of `U256` as a decimal string, to be used with `#[serde(with = "...")]`.
+///
+/// Deserialization accepts decimal strings, `0x`-prefixed hex strings and numbers.
+pub mod decimal {
+    use std::fmt;
+    use serde;
+    use super::U256;
+
+    /// Serializes the value as a decimal string.
+    pub fn serialize<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&value.to_dec_string())
+    }
+
+    /// Deserializes decimal or hex string or a number.
+    pub fn deserialize<'a, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: serde::Deserializer<'a>,
+    {
+        struct DecimalVisitor;
+
+        impl<'d> serde::de::Visitor<'d> for DecimalVisitor {
+            type Value = U256;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a decimal or 0x-prefixed hex number")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(value.into())
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                value
+                    .parse()
+                    .map_err(|e| E::custom(format!("Invalid number: {:?}", e)))
+            }
+        }
+
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+
+    /// Serialization of `Option<U256>` as a decimal string.
+    pub mod option {
+        use serde::{self, Deserialize};
+        use super::U256;
+
+        #[derive(Serialize, Deserialize)]
+        struct Decimal(#[serde(with = "super")] U256);
+
+        /// Serializes the value as a decimal string or `null`.
+        pub fn serialize<S>(value: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serde::Serialize::serialize(&value.map(Decimal), serializer)
+        }
+
+        /// Deserializes decimal or hex string, a number or `null`.
+        pub fn deserialize<'a, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+        where
+            D: serde::Deserializer<'a>,
+        {
+            Ok(Option::<Decimal>::deserialize(deserializer)?.map(|decimal| decimal.0))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{H128, U256};
+    use super::{FromStrError, H128, U256, U64};
     use serde_json;
 
     type Res = Result<U256, serde_json::Error>;
@@ -300,10 +1001,11 @@ mod tests {
         assert_eq!(&format!("{:?}", d), "0x2710");
 
         // Display
-        assert_eq!(&format!("{}", a), "0xa00010f00");
-        assert_eq!(&format!("{}", b), "0x3ff");
-        assert_eq!(&format!("{}", c), "0x0");
-        assert_eq!(&format!("{}", d), "0x2710");
+        assert_eq!(&format!("{}", a), "42949742336");
+        assert_eq!(&format!("{}", b), "1023");
+        assert_eq!(&format!("{}", c), "0");
+        assert_eq!(&format!("{}", d), "10000");
+        assert_eq!(&format!("{:>6}", b), "  1023");
 
         // Lowerhex
         assert_eq!(&format!("{:x}", a), "a00010f00");
@@ -400,4 +1102,141 @@ mod tests {
         assert_eq!(11u64, U256::from(11u64).low_u64());
         assert_eq!(111u64, U256::from(111u64).low_u64());
     }
+
+    #[test]
+    fn should_add_sub_and_mul() {
+        let max = U256::max_value();
+
+        assert_eq!(U256::from(2) + U256::from(3), U256::from(5));
+        assert_eq!(U256::from(u64::max_value()) + U256::from(1), U256::from_u128(1 << 64));
+        assert_eq!(max.checked_add(1.into()), None);
+        assert_eq!(max.wrapping_add(2.into()), U256::from(1));
+        assert_eq!(max.saturating_add(1.into()), max);
+
+        assert_eq!(U256::from(5) - U256::from(3), U256::from(2));
+        assert_eq!(U256::from(3).checked_sub(5.into()), None);
+        assert_eq!(U256::from(0).wrapping_sub(1.into()), max);
+        assert_eq!(U256::from(3).saturating_sub(5.into()), U256::zero());
+
+        let big = U256::from_u128(u128::max_value());
+        assert_eq!(big * big, max - (big << 1));
+        assert_eq!(max.checked_mul(2.into()), None);
+        assert_eq!(max.wrapping_mul(2.into()), max - U256::from(1));
+        assert_eq!((max >> 1).saturating_mul(3.into()), max);
+        assert_eq!(U64::from(1 << 40).checked_mul((1 << 30).into()), None);
+    }
+
+    #[test]
+    fn should_div_and_rem() {
+        let max = U256::max_value();
+        let big = U256::from_u128(u128::max_value());
+
+        assert_eq!(U256::from(17) / U256::from(5), U256::from(3));
+        assert_eq!(U256::from(17) % U256::from(5), U256::from(2));
+        assert_eq!(max / big, big + U256::from(2));
+        assert_eq!(max % big, U256::zero());
+        assert_eq!(max / max, U256::from(1));
+        assert_eq!(U256::from(7).checked_div(0.into()), None);
+        assert_eq!(U256::from(7).checked_rem(0.into()), None);
+        assert_eq!(U256::from(7).wrapping_rem(4.into()), U256::from(3));
+        assert_eq!(U256::from(7).saturating_div(4.into()), U256::from(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_panic_on_overflow() {
+        let _ = U256::max_value() + U256::from(1);
+    }
+
+    #[test]
+    fn should_shift_and_apply_bit_operations() {
+        let one = U256::from(1);
+
+        assert_eq!(one << 64, U256::from_u128(1 << 64));
+        assert_eq!((one << 255) >> 255, one);
+        assert_eq!(one << 256, U256::zero());
+        assert_eq!(U256::from(0xff00) >> 4, U256::from(0xff0));
+        assert_eq!(one.checked_shl(256), None);
+        assert_eq!(one.checked_shr(255), Some(U256::zero()));
+        assert_eq!((one << 200).bits(), 201);
+
+        assert_eq!(U256::from(0b1100) & U256::from(0b1010), U256::from(0b1000));
+        assert_eq!(U256::from(0b1100) | U256::from(0b1010), U256::from(0b1110));
+        assert_eq!(U256::from(0b1100) ^ U256::from(0b1010), U256::from(0b0110));
+        assert_eq!(!U256::zero(), U256::max_value());
+    }
+
+    #[test]
+    fn should_pow() {
+        assert_eq!(U256::from(10).pow(18), U256::from(1_000_000_000_000_000_000));
+        assert_eq!(U256::from(2).pow(255), U256::from(1) << 255);
+        assert_eq!(U256::from(2).checked_pow(256), None);
+        assert_eq!(U256::from(2).wrapping_pow(256), U256::zero());
+        assert_eq!(U256::from(2).saturating_pow(256), U256::max_value());
+        assert_eq!(U256::from(0).pow(0), U256::from(1));
+    }
+
+    #[test]
+    fn should_parse_and_format_decimals() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+        assert_eq!("0".parse::<U256>().unwrap(), U256::zero());
+        assert_eq!("1000000000000000000".parse::<U256>().unwrap(), U256::from(10).pow(18));
+        assert_eq!("0x3ff".parse::<U256>().unwrap(), U256::from(1023));
+        assert_eq!(max.parse::<U256>().unwrap(), U256::max_value());
+        assert_eq!(U256::max_value().to_string(), max);
+        assert_eq!((U256::from(10).pow(19)).to_string(), "10000000000000000000");
+        match "115792089237316195423570985008687907853269984665640564039457584007913129639936".parse::<U256>() {
+            Err(FromStrError::Overflow) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match "12a".parse::<U256>() {
+            Err(FromStrError::InvalidCharacter('a')) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!("".parse::<U256>().is_err());
+    }
+
+    #[test]
+    fn should_convert_u128_and_bytes() {
+        let value = U256::from_u128(u128::max_value() - 1);
+
+        assert_eq!(value.to_u128(), Some(u128::max_value() - 1));
+        assert_eq!((value + U256::from(2)).to_u128(), None);
+        assert_eq!((value + U256::from(2)).low_u128(), 0);
+        assert_eq!(U256::from(5).to_u64(), Some(5));
+
+        assert_eq!(U256::from_big_endian(&[1, 0]), Some(U256::from(256)));
+        assert_eq!(U256::from_big_endian(&[0; 40]), Some(U256::zero()));
+        assert_eq!(U256::from_big_endian(&[1; 33]), None);
+        assert_eq!(U256::from(&[1u8, 0][..]), U256::from(256));
+        assert_eq!(U256::from(256).to_big_endian()[30..], [1, 0]);
+    }
+
+    #[test]
+    fn should_serialize_decimal_strings() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Balance {
+            #[serde(with = "super::decimal")]
+            value: U256,
+            #[serde(with = "super::decimal::option")]
+            quota: Option<U256>,
+        }
+
+        let balance = Balance {
+            value: U256::from(10).pow(18),
+            quota: None,
+        };
+        let serialized = serde_json::to_string(&balance).unwrap();
+
+        assert_eq!(serialized, r#"{"value":"1000000000000000000","quota":null}"#);
+        assert_eq!(serde_json::from_str::<Balance>(&serialized).unwrap(), balance);
+        assert_eq!(
+            serde_json::from_str::<Balance>(r#"{"value":"0x10","quota":21000}"#).unwrap(),
+            Balance {
+                value: 16.into(),
+                quota: Some(21000.into()),
+            }
+        );
+    }
 }
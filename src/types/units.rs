@@ -0,0 +1,348 @@
+use std::{cmp, fmt};
+use std::str::FromStr;
+use types::U256;
+
+/// Maximal number of decimals of a unit, ERC-20 tokens report decimals as `uint8`.
+///
+/// Larger custom decimals are capped to avoid formatting (and allocating) absurdly long numbers.
+pub const MAX_DECIMALS: u32 = 255;
+
+/// Unit of token amounts, named units are Ether denominations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// 0 decimals
+    Wei,
+    /// 3 decimals
+    Kwei,
+    /// 6 decimals
+    Mwei,
+    /// 9 decimals
+    Gwei,
+    /// 12 decimals
+    Szabo,
+    /// 15 decimals
+    Finney,
+    /// 18 decimals
+    Ether,
+    /// Custom number of decimals (at most `MAX_DECIMALS`)
+    Decimals(u32),
+}
+
+impl Unit {
+    /// Returns number of decimals of the unit, custom decimals are capped to `MAX_DECIMALS`.
+    pub fn decimals(&self) -> u32 {
+        match *self {
+            Unit::Wei => 0,
+            Unit::Kwei => 3,
+            Unit::Mwei => 6,
+            Unit::Gwei => 9,
+            Unit::Szabo => 12,
+            Unit::Finney => 15,
+            Unit::Ether => 18,
+            Unit::Decimals(decimals) => cmp::min(decimals, MAX_DECIMALS),
+        }
+    }
+}
+
+impl From<u32> for Unit {
+    fn from(decimals: u32) -> Self {
+        Unit::Decimals(decimals)
+    }
+}
+
+impl FromStr for Unit {
+    type Err = UnitsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match &*s.to_lowercase() {
+            "wei" => Unit::Wei,
+            "kwei" => Unit::Kwei,
+            "mwei" => Unit::Mwei,
+            "gwei" => Unit::Gwei,
+            "szabo" => Unit::Szabo,
+            "finney" => Unit::Finney,
+            "ether" => Unit::Ether,
+            _ => return Err(UnitsError::UnknownUnit(s.into())),
+        })
+    }
+}
+
+/// Unit conversion error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnitsError {
+    /// The string is not a non-negative decimal number.
+    InvalidNumber(String),
+    /// The number has more non-zero fractional digits than the unit has decimals.
+    TooPrecise {
+        /// Decimals of the unit
+        decimals: u32,
+    },
+    /// The amount doesn't fit in `U256`.
+    Overflow,
+    /// Unknown unit name.
+    UnknownUnit(String),
+    /// The unit has more than `MAX_DECIMALS` decimals.
+    TooManyDecimals(u32),
+}
+
+impl fmt::Display for UnitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnitsError::InvalidNumber(ref number) => write!(f, "Invalid number: {}", number),
+            UnitsError::TooPrecise { decimals } => write!(f, "Number has more than {} decimals", decimals),
+            UnitsError::Overflow => write!(f, "Amount is too large"),
+            UnitsError::UnknownUnit(ref unit) => write!(f, "Unknown unit: {}", unit),
+            UnitsError::TooManyDecimals(decimals) => {
+                write!(f, "Unit has {} decimals, at most {} are supported", decimals, MAX_DECIMALS)
+            }
+        }
+    }
+}
+
+/// Converts decimal number in given unit (e.g. `"1.5"` ether) to base units.
+///
+/// The conversion is exact: trailing fractional zeros are ignored,
+/// but any other digit beyond the decimals of the unit is an error (nothing is rounded).
+/// Units with more than `MAX_DECIMALS` decimals are rejected.
+pub fn parse_units<U: Into<Unit>>(value: &str, unit: U) -> Result<U256, UnitsError> {
+    let decimals = match unit.into() {
+        Unit::Decimals(decimals) if decimals > MAX_DECIMALS => return Err(UnitsError::TooManyDecimals(decimals)),
+        unit => unit.decimals() as usize,
+    };
+    let invalid = || UnitsError::InvalidNumber(value.into());
+
+    let (integer, fraction) = match value.find('.') {
+        Some(idx) => (&value[..idx], &value[idx + 1..]),
+        None => (value, ""),
+    };
+    if integer.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !integer.chars().chain(fraction.chars()).all(|c| c.is_digit(10)) {
+        return Err(invalid());
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals {
+        return Err(UnitsError::TooPrecise {
+            decimals: decimals as u32,
+        });
+    }
+
+    let mut digits = String::with_capacity(integer.len() + decimals);
+    digits.push_str(integer);
+    digits.push_str(fraction);
+    digits.extend(::std::iter::repeat('0').take(decimals - fraction.len()));
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(U256::zero());
+    }
+
+    U256::from_dec_str(digits).map_err(|_| UnitsError::Overflow)
+}
+
+/// Splits decimal digits of the value into integer and fractional part.
+fn split_digits(value: U256, decimals: usize) -> (String, String) {
+    let digits = value.to_dec_string();
+    if decimals == 0 {
+        return (digits, String::new());
+    }
+
+    let padded = if digits.len() <= decimals {
+        format!("{}{}", "0".repeat(decimals + 1 - digits.len()), digits)
+    } else {
+        digits
+    };
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    (integer.into(), fraction.into())
+}
+
+/// Formats base units amount as a decimal number in given unit (e.g. `"1.5"` ether).
+///
+/// The result is exact, trailing fractional zeros are omitted.
+pub fn format_units<U: Into<Unit>>(value: U256, unit: U) -> String {
+    let (integer, fraction) = split_digits(value, unit.into().decimals() as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Formats base units amount as a decimal number in given unit with exactly `precision` fractional digits.
+///
+/// Amounts are rounded half up, i.e. `1.25` with precision `1` becomes `1.3`.
+/// Precision is capped to `MAX_DECIMALS`.
+pub fn format_units_rounded<U: Into<Unit>>(value: U256, unit: U, precision: u32) -> String {
+    let decimals = unit.into().decimals();
+    let precision = cmp::min(precision, MAX_DECIMALS);
+    let (value, decimals) = if precision >= decimals {
+        (value, decimals)
+    } else {
+        match U256::from(10).checked_pow(decimals - precision) {
+            Some(divisor) => {
+                let (quotient, remainder) = value.div_mod(divisor);
+                // Quotient is at most `max / 10`, so incrementing never overflows.
+                if remainder >= divisor - remainder {
+                    (quotient + U256::from(1), precision)
+                } else {
+                    (quotient, precision)
+                }
+            }
+            // Divisor is greater than twice any `U256` value.
+            None => (U256::zero(), precision),
+        }
+    };
+
+    let (integer, mut fraction) = split_digits(value, decimals as usize);
+    fraction.extend(::std::iter::repeat('0').take((precision - decimals) as usize));
+    if fraction.is_empty() {
+        integer
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Amount of tokens in base units together with the number of decimals of the token.
+///
+/// Tokenizes as `uint256` of base units. Decoding loses the decimals (they are not part of the token),
+/// so decoded amounts are in base units; use `with_decimals` to interpret them in token units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amount {
+    value: U256,
+    decimals: u32,
+}
+
+impl Amount {
+    /// Creates amount given value in base units.
+    pub fn new<U: Into<Unit>>(value: U256, unit: U) -> Self {
+        Amount {
+            value,
+            decimals: unit.into().decimals(),
+        }
+    }
+
+    /// Parses decimal number in given unit.
+    pub fn parse<U: Into<Unit>>(value: &str, unit: U) -> Result<Self, UnitsError> {
+        let unit = unit.into();
+        Ok(Self::new(parse_units(value, unit)?, unit))
+    }
+
+    /// Returns value in base units.
+    pub fn value(&self) -> U256 {
+        self.value
+    }
+
+    /// Returns number of decimals.
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// Returns the same value in base units with different number of decimals.
+    pub fn with_decimals<U: Into<Unit>>(self, unit: U) -> Self {
+        Self::new(self.value, unit)
+    }
+
+    /// Formats the amount with exactly `precision` fractional digits (rounded half up).
+    pub fn format_rounded(&self, precision: u32) -> String {
+        format_units_rounded(self.value, self.decimals, precision)
+    }
+}
+
+impl From<Amount> for U256 {
+    fn from(amount: Amount) -> Self {
+        amount.value
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&format_units(self.value, self.decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::U256;
+    use super::{format_units, format_units_rounded, parse_units, Amount, Unit, UnitsError, MAX_DECIMALS};
+
+    fn ether(value: u64) -> U256 {
+        U256::from(value) * U256::from(10).pow(18)
+    }
+
+    #[test]
+    fn should_parse_units() {
+        assert_eq!(parse_units("1.5", Unit::Ether), Ok(ether(15) / U256::from(10)));
+        assert_eq!(parse_units("1", Unit::Ether), Ok(ether(1)));
+        assert_eq!(parse_units(".5", Unit::Gwei), Ok(U256::from(500_000_000)));
+        assert_eq!(parse_units("2.", 2), Ok(U256::from(200)));
+        assert_eq!(parse_units("0.000", Unit::Wei), Ok(U256::zero()));
+        assert_eq!(parse_units("1.2300", 2), Ok(U256::from(123)));
+        assert_eq!(parse_units("007", Unit::Kwei), Ok(U256::from(7000)));
+    }
+
+    #[test]
+    fn should_reject_invalid_amounts() {
+        assert_eq!(parse_units("1.001", 2), Err(UnitsError::TooPrecise { decimals: 2 }));
+        assert_eq!(parse_units("1.5", Unit::Wei), Err(UnitsError::TooPrecise { decimals: 0 }));
+        assert_eq!(parse_units("", 2), Err(UnitsError::InvalidNumber("".into())));
+        assert_eq!(parse_units(".", 2), Err(UnitsError::InvalidNumber(".".into())));
+        assert_eq!(parse_units("-1", 2), Err(UnitsError::InvalidNumber("-1".into())));
+        assert_eq!(parse_units("1e18", 2), Err(UnitsError::InvalidNumber("1e18".into())));
+        assert_eq!(parse_units("1.2.3", 2), Err(UnitsError::InvalidNumber("1.2.3".into())));
+        assert_eq!(parse_units(&"9".repeat(60), Unit::Ether), Err(UnitsError::Overflow));
+    }
+
+    #[test]
+    fn should_format_units() {
+        assert_eq!(format_units(ether(15) / U256::from(10), Unit::Ether), "1.5");
+        assert_eq!(format_units(ether(2), Unit::Ether), "2");
+        assert_eq!(format_units(U256::from(1), Unit::Ether), "0.000000000000000001");
+        assert_eq!(format_units(U256::zero(), Unit::Gwei), "0");
+        assert_eq!(format_units(U256::from(123), Unit::Wei), "123");
+        assert_eq!(format_units(U256::from(123), 100), format!("0.{}123", "0".repeat(97)));
+    }
+
+    #[test]
+    fn should_round_half_up() {
+        assert_eq!(format_units_rounded(U256::from(125), 2, 1), "1.3");
+        assert_eq!(format_units_rounded(U256::from(124), 2, 1), "1.2");
+        assert_eq!(format_units_rounded(U256::from(995), 3, 2), "1.00");
+        assert_eq!(format_units_rounded(U256::from(5), 1, 0), "1");
+        assert_eq!(format_units_rounded(U256::from(15), 1, 4), "1.5000");
+        assert_eq!(format_units_rounded(U256::max_value(), 100, 2), "0.00");
+        assert_eq!(format_units_rounded(U256::max_value(), 0, 0), U256::max_value().to_string());
+    }
+
+    #[test]
+    fn should_cap_decimals() {
+        assert_eq!(Unit::Decimals(u32::max_value()).decimals(), MAX_DECIMALS);
+        assert_eq!(
+            parse_units("0", u32::max_value()),
+            Err(UnitsError::TooManyDecimals(u32::max_value()))
+        );
+        assert_eq!(format_units(U256::from(1), u32::max_value()).len(), MAX_DECIMALS as usize + 2);
+        assert_eq!(format_units_rounded(U256::from(1), 0, u32::max_value()).len(), MAX_DECIMALS as usize + 2);
+        assert_eq!(Amount::new(U256::from(1), u32::max_value()).decimals(), MAX_DECIMALS);
+    }
+
+    #[test]
+    fn should_parse_unit_names() {
+        assert_eq!("Gwei".parse(), Ok(Unit::Gwei));
+        assert_eq!("ether".parse::<Unit>().map(|unit| unit.decimals()), Ok(18));
+        assert_eq!("eth".parse::<Unit>(), Err(UnitsError::UnknownUnit("eth".into())));
+    }
+
+    #[test]
+    fn should_carry_decimals_in_amount() {
+        let amount = Amount::parse("12.5", 6).unwrap();
+
+        assert_eq!(amount.value(), U256::from(12_500_000));
+        assert_eq!(amount.decimals(), 6);
+        assert_eq!(amount.to_string(), "12.5");
+        assert_eq!(amount.format_rounded(0), "13");
+        assert_eq!(amount.with_decimals(Unit::Wei).to_string(), "12500000");
+        assert_eq!(U256::from(amount), U256::from(12_500_000));
+    }
+}
@@ -23,8 +23,17 @@ impl<'a> Deserialize<'a> for Work {
     {
         let v: Value = Deserialize::deserialize(deserializer)?;
 
-        let (pow_hash, seed_hash, target, number) = serde_json::from_value::<(H256, H256, H256, u64)>(v.clone())
-            .map(|(pow_hash, seed_hash, target, number)| (pow_hash, seed_hash, target, Some(number)))
+        // The number is a hex quantity (as serialized below) or a plain integer.
+        let (pow_hash, seed_hash, target, number) = serde_json::from_value::<(H256, H256, H256, U256)>(v.clone())
+            .map_err(|e| e.to_string())
+            .and_then(|(pow_hash, seed_hash, target, number)| match number.to_u64() {
+                Some(number) => Ok((pow_hash, seed_hash, target, Some(number))),
+                None => Err(format!("Block number too large: {}", number)),
+            })
+            .or_else(|_| {
+                serde_json::from_value::<(H256, H256, H256, u64)>(v.clone())
+                    .map(|(pow_hash, seed_hash, target, number)| (pow_hash, seed_hash, target, Some(number)))
+            })
             .or_else(|_| {
                 serde_json::from_value::<(H256, H256, H256)>(v)
                     .map(|(pow_hash, seed_hash, target)| (pow_hash, seed_hash, target, None))